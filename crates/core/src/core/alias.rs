@@ -0,0 +1,187 @@
+use super::{selection::select_counted, CostStrategy, TokenAllocator};
+
+///
+/// A member chain of a global, aliased by a hoisted variable.
+///
+/// ```unknown
+/// Object.defineProperty => a, a=Object.defineProperty
+/// ```
+///
+/// A called chain is bound to its object, so `this` is the same.
+///
+/// ```unknown
+/// document.createElement("div") => a("div"), a=document.createElement.bind(document)
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChainAlias {
+    pub chain: String,
+    pub bound: bool,
+}
+
+impl ChainAlias {
+    pub fn new(chain: &str, bound: bool) -> Self {
+        Self {
+            chain: chain.to_string(),
+            bound,
+        }
+    }
+
+    /// `document` of `document.createElement`
+    pub fn object(&self) -> &str {
+        self.chain
+            .rsplit_once('.')
+            .map_or(self.chain.as_str(), |(object, _)| object)
+    }
+
+    // a=Object.defineProperty, or a=document.createElement.bind(document),
+    fn declaration_cost(&self, name: &str) -> usize {
+        let init = if self.bound {
+            self.chain.len() + ".bind(".len() + self.object().len() + 1
+        } else {
+            self.chain.len()
+        };
+
+        name.len() + 1 + init + 1
+    }
+
+    pub fn saved_bytes(&self, name: &str, count: usize) -> isize {
+        // Object.defineProperty => a
+        let saved = (self.chain.len() as isize - name.len() as isize) * count as isize;
+
+        saved - self.declaration_cost(name) as isize
+    }
+
+    // const a=this.props.store;
+    fn local_declaration_cost(&self, name: &str) -> usize {
+        "const ".len() + name.len() + 1 + self.chain.len() + 1
+    }
+
+    /// the bytes saved by a `const` aliasing the chain in a function, see [`select_local_alias`]
+    pub fn local_saved_bytes(&self, name: &str, count: usize) -> isize {
+        let saved = (self.chain.len() as isize - name.len() as isize) * count as isize;
+
+        saved - self.local_declaration_cost(name) as isize
+    }
+}
+
+///
+/// Returns the aliases worth it and their names, taken from `allocator` in order.
+///
+/// `candidates` are the aliases and how many times each of them is used.
+///
+pub fn select_aliases(
+    candidates: Vec<(ChainAlias, usize)>,
+    allocator: &TokenAllocator,
+    strategy: &CostStrategy,
+) -> Vec<(ChainAlias, String)> {
+    select_counted(candidates, allocator, strategy, |alias, name, count| {
+        alias.saved_bytes(name, count)
+    })
+}
+
+///
+/// Returns the chain of a function worth a local `const` named `name` the most, the chains
+/// are never bound.
+///
+/// `candidates` are the chains and how many times each of them is read.
+///
+pub fn select_local_alias(
+    candidates: Vec<(ChainAlias, usize)>,
+    name: &str,
+    strategy: &CostStrategy,
+) -> Option<ChainAlias> {
+    let thresholds = strategy.thresholds();
+
+    candidates
+        .into_iter()
+        .filter(|(_, count)| *count >= thresholds.min_occurrences)
+        .map(|(alias, count)| (alias.local_saved_bytes(name, count), alias))
+        .filter(|(saved, _)| *saved >= thresholds.min_saved_bytes as isize)
+        // the shorter chain on ties, then the first one in order
+        .max_by(|(a, a_alias), (b, b_alias)| {
+            a.cmp(b)
+                .then_with(|| b_alias.chain.len().cmp(&a_alias.chain.len()))
+                .then_with(|| b_alias.cmp(a_alias))
+        })
+        .map(|(_, alias)| alias)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_bytes() {
+        let alias = ChainAlias::new("Object.defineProperty", false);
+
+        // a=Object.defineProperty,
+        assert_eq!(alias.saved_bytes("a", 2), 2 * 20 - 24);
+
+        let alias = ChainAlias::new("document.createElement", true);
+
+        assert_eq!(alias.object(), "document");
+        // a=document.createElement.bind(document),
+        assert_eq!(alias.saved_bytes("a", 2), 2 * 21 - 40);
+    }
+
+    #[test]
+    fn local_saved_bytes() {
+        let alias = ChainAlias::new("this.props.store", false);
+
+        // const a=this.props.store;
+        assert_eq!(alias.local_saved_bytes("a", 3), 3 * 15 - 25);
+    }
+
+    #[test]
+    fn select_local() {
+        let chain = |chain: &str| ChainAlias::new(chain, false);
+        let strategy = CostStrategy::default();
+
+        assert_eq!(
+            select_local_alias(
+                vec![
+                    (chain("this.props"), 6),
+                    (chain("this.props.store.state"), 4),
+                    (chain("this.props.store.state.user"), 2),
+                ],
+                "a",
+                &strategy,
+            ),
+            Some(chain("this.props.store.state"))
+        );
+
+        // const a=this.b; is longer than the 2 reads
+        assert_eq!(
+            select_local_alias(vec![(chain("this.b"), 2)], "a", &strategy),
+            None
+        );
+    }
+
+    #[test]
+    fn select() {
+        let selected = select_aliases(
+            vec![
+                (ChainAlias::new("Object.defineProperty", false), 10),
+                (ChainAlias::new("document.createElement", true), 2),
+                (ChainAlias::new("Array.prototype.slice", false), 1),
+            ],
+            &TokenAllocator::new(),
+            &CostStrategy::default(),
+        );
+
+        assert_eq!(
+            selected,
+            vec![
+                (
+                    ChainAlias::new("Object.defineProperty", false),
+                    "a".to_string()
+                ),
+                (
+                    ChainAlias::new("document.createElement", true),
+                    "b".to_string()
+                ),
+            ]
+        );
+    }
+}
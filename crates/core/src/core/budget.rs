@@ -0,0 +1,144 @@
+use itertools::Itertools;
+
+use super::{
+    compress_ident::{CostCalculator, HostingVariable},
+    CostStrategy, Occurrences, TokenAllocator,
+};
+
+///
+/// Keeps the least risky candidates, until the output fits `budget`.
+///
+/// A candidate is riskier the more usages it rewrites for each byte it saves, so the ones
+/// touching the fewest sites are kept first. `measure` returns the size of the output with the
+/// given candidates hoisted, the shortest prefix of that order that fits is searched for.
+/// Nothing is hoisted when the output already fits, and everything is when it never fits.
+///
+/// The kept candidates stay in the order of `ranked`.
+///
+pub fn fit_size_budget<M>(
+    mut ranked: Vec<(String, Occurrences)>,
+    allocator: &TokenAllocator,
+    ascii_only: bool,
+    strategy: &CostStrategy,
+    budget: usize,
+    mut measure: M,
+) -> Vec<(String, Occurrences)>
+where
+    M: FnMut(&[(String, Occurrences)]) -> usize,
+{
+    let calculator = HostingVariable::new(ascii_only, strategy);
+    let mut allocator = allocator.clone();
+
+    // (saved bytes, rewritten usages)
+    let estimates = ranked
+        .iter()
+        .map_while(|(value, occurrences)| {
+            let name = allocator.alloc_for(value)?;
+            let sites = occurrences
+                .iter()
+                .filter(|(kind, _)| calculator.should_replace(&name, value, *kind))
+                .map(|(_, count)| count)
+                .sum::<usize>();
+
+            Some((calculator.saved_bytes(&name, value, occurrences), sites))
+        })
+        .collect::<Vec<_>>();
+
+    // the candidates left without a name are never hoisted
+    ranked.truncate(estimates.len());
+
+    let order = (0..ranked.len())
+        .sorted_by(|a, b| {
+            let (a_saved, a_sites) = estimates[*a];
+            let (b_saved, b_sites) = estimates[*b];
+
+            // a_sites / a_saved < b_sites / b_saved
+            (a_sites as isize * b_saved)
+                .cmp(&(b_sites as isize * a_saved))
+                .then(a.cmp(b))
+        })
+        .collect::<Vec<_>>();
+
+    let prefix = |len: usize| {
+        let mut kept = vec![false; ranked.len()];
+
+        order[..len].iter().for_each(|i| kept[*i] = true);
+
+        ranked
+            .iter()
+            .zip(kept)
+            .filter(|(_, kept)| *kept)
+            .map(|(candidate, _)| candidate.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // the smallest prefix that fits, in lo..=hi
+    let (mut lo, mut hi) = (0, ranked.len());
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+
+        if measure(&prefix(mid)) <= budget {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+
+    prefix(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::OccurrenceKind;
+
+    fn members(value: &str, count: usize) -> (String, Occurrences) {
+        (
+            value.to_string(),
+            Occurrences::new().with(OccurrenceKind::Member, count),
+        )
+    }
+
+    fn fit(budget: usize) -> Vec<String> {
+        let ranked = vec![
+            // saves 189 bytes with 50 usages
+            members("longer", 50),
+            // saves 145 bytes with 5 usages
+            members(&"a".repeat(40), 5),
+        ];
+
+        fit_size_budget(
+            ranked,
+            &TokenAllocator::new(),
+            false,
+            &CostStrategy::default(),
+            budget,
+            |selected| {
+                1000 - selected
+                    .iter()
+                    .map(|(value, _)| if value == "longer" { 189 } else { 145 })
+                    .sum::<usize>()
+            },
+        )
+        .into_iter()
+        .map(|(value, _)| value)
+        .collect()
+    }
+
+    #[test]
+    fn nothing_when_it_already_fits() {
+        assert_eq!(fit(1000), Vec::<String>::new());
+    }
+
+    #[test]
+    fn least_risky_first() {
+        assert_eq!(fit(900), vec!["a".repeat(40)]);
+    }
+
+    #[test]
+    fn keep_rank_order() {
+        assert_eq!(fit(700), vec!["longer".to_string(), "a".repeat(40)]);
+        assert_eq!(fit(0), vec!["longer".to_string(), "a".repeat(40)]);
+    }
+}
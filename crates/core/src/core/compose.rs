@@ -0,0 +1,252 @@
+use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::{
+    compress_ident::{CostCalculator, HostingVariable},
+    CostStrategy, OccurrenceKind, Occurrences,
+};
+
+///
+/// A prefix or a suffix shared by several strings, hoisted on its own.
+///
+/// ```unknown
+/// "./components/Button" => a+"Button"
+/// "./components/Input"  => a+"Input"
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Affix {
+    pub value: String,
+    /// [`OccurrenceKind::Prefix`] or [`OccurrenceKind::Suffix`]
+    pub kind: OccurrenceKind,
+    /// the strings starting or ending with `value`, longer than it
+    pub strings: Vec<String>,
+}
+
+///
+/// Finds the prefixes and suffixes worth hoisting, from the strings of `map` used as
+/// [`OccurrenceKind::Lit`].
+///
+/// The strings worth hoisting on their own are left out, and a string is only split once.
+/// The candidates are the longest prefixes (and suffixes) shared by two neighbouring strings
+/// once sorted, the one saving the most bytes is taken first, until none is worth it.
+///
+pub fn find_affixes(
+    map: &FxHashMap<String, Occurrences>,
+    ascii_only: bool,
+    strategy: &CostStrategy,
+) -> Vec<Affix> {
+    let calculator = HostingVariable::new(ascii_only, strategy);
+    // the shortest name, no affix can do better
+    let name = "a";
+
+    let strings = map
+        .iter()
+        .filter(|(_, occurrences)| occurrences.count(OccurrenceKind::Lit) > 0)
+        .filter(|(value, occurrences)| {
+            !(calculator.is_candidate(value, occurrences)
+                && calculator.should_compress(name, value, occurrences))
+        })
+        .map(|(value, occurrences)| (value.as_str(), occurrences.count(OccurrenceKind::Lit)))
+        .collect::<Vec<_>>();
+
+    let prefixes = Keys::new(&strings, OccurrenceKind::Prefix);
+    let suffixes = Keys::new(&strings, OccurrenceKind::Suffix);
+
+    let candidates = [&prefixes, &suffixes]
+        .into_iter()
+        .flat_map(|keys| keys.candidates().map(move |key| (keys, key)))
+        .collect::<Vec<_>>();
+
+    let mut taken = FxHashSet::default();
+    let mut affixes = vec![];
+
+    loop {
+        let best = candidates
+            .iter()
+            .filter_map(|(keys, key)| {
+                let members = keys
+                    .members(key)
+                    .filter(|(string, _)| !taken.contains(string))
+                    .collect::<Vec<_>>();
+                let value = keys.restore(key);
+                let occurrences = Occurrences::new().with(
+                    keys.kind,
+                    members.iter().map(|(_, count)| count).sum::<usize>(),
+                );
+
+                if !calculator.is_candidate(&value, &occurrences)
+                    || !calculator.should_compress(name, &value, &occurrences)
+                {
+                    return None;
+                }
+
+                let saved = calculator.saved_bytes(name, &value, &occurrences);
+
+                Some((saved, value, keys.kind, members))
+            })
+            // the smallest affix wins on ties, whatever the order of the candidates
+            .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)));
+
+        let Some((_, value, kind, members)) = best else {
+            break;
+        };
+
+        let strings = members
+            .into_iter()
+            .map(|(string, _)| {
+                taken.insert(string);
+                string.to_string()
+            })
+            .sorted()
+            .collect();
+
+        affixes.push(Affix {
+            value,
+            kind,
+            strings,
+        });
+    }
+
+    affixes
+}
+
+///
+/// The strings sorted by their key, the string itself for prefixes and the reversed string
+/// for suffixes, so the strings sharing an affix are next to each other.
+///
+struct Keys<'a> {
+    kind: OccurrenceKind,
+    // (key, string, usages)
+    sorted: Vec<(String, &'a str, usize)>,
+}
+
+impl<'a> Keys<'a> {
+    fn new(strings: &[(&'a str, usize)], kind: OccurrenceKind) -> Self {
+        let sorted = strings
+            .iter()
+            .map(|(string, count)| {
+                let key = match kind {
+                    OccurrenceKind::Suffix => string.chars().rev().collect(),
+                    _ => string.to_string(),
+                };
+
+                (key, *string, *count)
+            })
+            .sorted()
+            .collect();
+
+        Self { kind, sorted }
+    }
+
+    fn restore(&self, key: &str) -> String {
+        match self.kind {
+            OccurrenceKind::Suffix => key.chars().rev().collect(),
+            _ => key.to_string(),
+        }
+    }
+
+    /// the longest common prefix of each two neighbouring keys
+    fn candidates(&self) -> impl Iterator<Item = String> + '_ {
+        self.sorted
+            .iter()
+            .tuple_windows()
+            .map(|((a, _, _), (b, _, _))| {
+                let len = a
+                    .char_indices()
+                    .zip(b.chars())
+                    .take_while(|((_, x), y)| x == y)
+                    .last()
+                    .map_or(0, |((i, ch), _)| i + ch.len_utf8());
+
+                a[..len].to_string()
+            })
+            .filter(|key| !key.is_empty())
+            .unique()
+    }
+
+    /// the strings whose key starts with `key`, and is longer than it
+    fn members<'b>(&'b self, key: &'b str) -> impl Iterator<Item = (&'a str, usize)> + 'b {
+        let start = self
+            .sorted
+            .partition_point(|(other, _, _)| other.as_str() < key);
+
+        self.sorted[start..]
+            .iter()
+            .take_while(move |(other, _, _)| other.starts_with(key))
+            .filter(move |(other, _, _)| other.len() > key.len())
+            .map(|(_, string, count)| (*string, *count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lits(strings: &[(&str, usize)]) -> FxHashMap<String, Occurrences> {
+        strings
+            .iter()
+            .map(|(value, count)| {
+                (
+                    value.to_string(),
+                    Occurrences::new().with(OccurrenceKind::Lit, *count),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prefix() {
+        let map = lits(&[
+            ("./components/Button", 1),
+            ("./components/Input", 1),
+            ("./components/Select", 1),
+            ("./utils", 1),
+        ]);
+
+        let affixes = find_affixes(&map, false, &CostStrategy::default());
+
+        assert_eq!(
+            affixes,
+            vec![Affix {
+                value: "./components/".to_string(),
+                kind: OccurrenceKind::Prefix,
+                strings: vec![
+                    "./components/Button".to_string(),
+                    "./components/Input".to_string(),
+                    "./components/Select".to_string(),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn suffix() {
+        let map = lits(&[
+            ("userController", 1),
+            ("orderController", 1),
+            ("itemController", 1),
+        ]);
+
+        let affixes = find_affixes(&map, false, &CostStrategy::default());
+
+        assert_eq!(affixes.len(), 1);
+        assert_eq!(affixes[0].value, "Controller");
+        assert_eq!(affixes[0].kind, OccurrenceKind::Suffix);
+    }
+
+    #[test]
+    fn skip_strings_worth_hoisting() {
+        // hoisted as a whole
+        let map = lits(&[("./components/Button", 5), ("./components/Input", 1)]);
+
+        assert_eq!(find_affixes(&map, false, &CostStrategy::default()), vec![]);
+    }
+
+    #[test]
+    fn nothing_when_not_worth_it() {
+        let map = lits(&[("./a/b", 1), ("./a/c", 1)]);
+
+        assert_eq!(find_affixes(&map, false, &CostStrategy::default()), vec![]);
+    }
+}
@@ -1,9 +1,17 @@
-use std::cmp::Reverse;
+use std::io::Write;
 
+use flate2::{write::GzEncoder, Compression};
 use itertools::Itertools;
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 
-use super::constant;
+use super::{
+    occurrence::{OccurrenceKind, Occurrences},
+    printed_len::{ident_len, quoted_len},
+    selection::solve_with_threshold,
+    strategy::{CostPreset, CostStrategy, Thresholds},
+    TokenAllocator,
+};
 
 #[allow(dead_code)]
 struct ConstantCost {
@@ -11,118 +19,648 @@ struct ConstantCost {
     more_cost: isize,
 }
 
-struct HostingVariable;
+///
+/// Hoists the strings into a `var` declaration at the top of the module.
+///
+pub struct HostingVariable {
+    ascii_only: bool,
+    thresholds: Thresholds,
+}
+
+impl HostingVariable {
+    pub fn new(ascii_only: bool, strategy: &CostStrategy) -> Self {
+        Self {
+            ascii_only,
+            thresholds: strategy.thresholds(),
+        }
+    }
+}
 
 #[allow(dead_code)]
 struct HostingCustom(ConstantCost);
 
-const VAR_HOSTING: HostingVariable = HostingVariable;
+/// `var ;` is shared by all declarators, and the last declarator has no `,`
+pub(super) const DECLARATION_FIXED_COST: usize = 4;
+
+///
+/// Decides which strings are hoisted.
+///
+/// Implementors only have to tell the bytes of a declaration and of one usage, the
+/// selection itself is shared, see [`CostCalculator::select`].
+///
+pub trait CostCalculator: Sized {
+    /// bytes of the declarator
+    fn declaration_cost(&self, name: &str, value: &str) -> usize;
+
+    /// bytes of the declarator of a constant, printed as it is written
+    fn constant_declaration_cost(&self, name: &str, value: &str) -> usize {
+        name.len() + 1 + value.len() + 1
+    }
 
-#[allow(dead_code)]
-trait CostCalculator: Sized {
-    // fn create(pos: usize, ident_len: usize, used_counts: usize) -> Self;
-    fn first_cost() -> usize;
-    fn more_cost() -> isize;
-    fn should_compress(&self, pos: usize, ident_len: usize, used_counts: usize) -> bool;
-}
+    /// bytes of one usage, before and after replacing
+    fn usage_cost(&self, name: &str, value: &str, kind: OccurrenceKind) -> (usize, usize);
 
-impl CostCalculator for HostingVariable {
-    // .foo => var a = "foo"; [a]
-    // first usage cost `="",[]`
-    fn first_cost() -> usize {
-        6
+    /// whether a single usage gets shorter, the ones that do not are left as they are
+    fn should_replace(&self, name: &str, value: &str, kind: OccurrenceKind) -> bool {
+        let (before, after) = self.usage_cost(name, value, kind);
+
+        after < before
     }
 
-    // static cost
-    // ._ => [_]
-    fn more_cost() -> isize {
-        2 - 1
+    fn saved_bytes(&self, name: &str, value: &str, occurrences: &Occurrences) -> isize {
+        let saved = occurrences
+            .iter()
+            .map(|(kind, count)| {
+                let (before, after) = self.usage_cost(name, value, kind);
+
+                (before as isize - after as isize).max(0) * count as isize
+            })
+            .sum::<isize>();
+
+        let declaration = if occurrences.is_constant() {
+            self.constant_declaration_cost(name, value)
+        } else {
+            self.declaration_cost(name, value)
+        };
+
+        saved - declaration as isize
     }
 
-    fn should_compress(&self, pos: usize, ident_len: usize, used_counts: usize) -> bool {
-        let pos = pos as isize;
-        let ch_len = ident_len as isize;
-        let used_counts = used_counts as isize;
+    fn thresholds(&self) -> Thresholds {
+        CostPreset::Balanced.thresholds()
+    }
 
-        // predict the length after compressing ch.
-        let cost = (pos / constant::COMPRESS_CHARACTER_WIDTH as isize).max(1);
+    /// whether `value` is considered at all, before any name is given to it
+    fn is_candidate(&self, value: &str, occurrences: &Occurrences) -> bool {
+        let thresholds = self.thresholds();
 
-        // Fixed cost
-        // The cost of var, now var is not calculated
-        let v1 = (Self::first_cost() as isize) + (cost * 2) - 1;
+        value.len() >= thresholds.min_length && occurrences.total() >= thresholds.min_occurrences
+    }
 
-        // cost of subsequent use .a => [a], cost: 1, more_cost: -1 ch_len: xxx.len
-        let v2 = (cost + Self::more_cost()) - ch_len;
-        // all cost
-        let v3 = v2 * (used_counts - 1);
+    fn should_compress(&self, name: &str, value: &str, occurrences: &Occurrences) -> bool {
+        self.saved_bytes(name, value, occurrences) >= self.thresholds().min_saved_bytes as isize
+    }
+
+    ///
+    /// Returns the strings worth hoisting, ranked by the name they should take from `allocator`.
+    ///
+    fn select(
+        &mut self,
+        map: FxHashMap<String, Occurrences>,
+        allocator: &TokenAllocator,
+    ) -> Vec<(String, Occurrences)> {
+        select_by_estimate(self, map, allocator)
+    }
+}
+
+impl CostCalculator for HostingVariable {
+    fn thresholds(&self) -> Thresholds {
+        self.thresholds
+    }
 
-        v1 + v3 < 0
+    // foo => a="foo",
+    fn declaration_cost(&self, name: &str, value: &str) -> usize {
+        name.len() + 1 + quoted_len(value, self.ascii_only) + 1
+    }
+
+    fn usage_cost(&self, name: &str, value: &str, kind: OccurrenceKind) -> (usize, usize) {
+        match kind {
+            // .foo => [a]
+            OccurrenceKind::Member => (1 + ident_len(value, self.ascii_only), name.len() + 2),
+            // "./foo" => a
+            OccurrenceKind::Lit => (quoted_len(value, self.ascii_only), name.len()),
+            // foo: => [a]:
+            OccurrenceKind::Key => (ident_len(value, self.ascii_only), name.len() + 2),
+            // "./foo": => [a]:
+            OccurrenceKind::LitKey => (quoted_len(value, self.ascii_only), name.len() + 2),
+            // { foo } => { [a]: foo }
+            OccurrenceKind::Shorthand => {
+                let len = ident_len(value, self.ascii_only);
+
+                (len, name.len() + 3 + len)
+            }
+            // `foo${x}` => `${a}${x}`
+            OccurrenceKind::Quasi => (quoted_len(value, self.ascii_only) - 2, name.len() + 3),
+            // "foo/bar" => a+"bar"
+            OccurrenceKind::Prefix | OccurrenceKind::Suffix => {
+                (quoted_len(value, self.ascii_only) - 2, name.len() + 1)
+            }
+            // title="foo" => title={a}
+            OccurrenceKind::JsxAttr => (quoted_len(value, self.ascii_only), name.len() + 2),
+            // <div> => <A>, but <a> is the `a` element
+            OccurrenceKind::JsxElement => {
+                let len = ident_len(value, self.ascii_only);
+
+                if name.starts_with(|ch: char| ch.is_ascii_lowercase()) {
+                    (len, len)
+                } else {
+                    (len, name.len())
+                }
+            }
+            // 4294967295 => a
+            OccurrenceKind::Constant => (value.len(), name.len()),
+        }
     }
 }
 
-pub fn filter_cannot_compress_ident(map: FxHashMap<String, usize>) -> FxHashMap<String, usize> {
-    let (v1, mut v): (Vec<_>, Vec<_>) = map
+///
+/// The names are taken from `allocator` in order, so the cost is calculated with the name
+/// that the replacer will actually use. See [`solve_with_threshold`] for how the candidates
+/// are chosen.
+///
+/// A string with a name in the cache of `allocator` keeps it, so it is priced with that name
+/// and does not take one from the others.
+///
+/// The result is ranked, the candidates should take their names from
+/// [`TokenAllocator::alloc_for`] in order.
+///
+fn select_by_estimate<C: CostCalculator>(
+    calculator: &C,
+    map: FxHashMap<String, Occurrences>,
+    allocator: &TokenAllocator,
+) -> Vec<(String, Occurrences)> {
+    let min_saved = calculator.thresholds().min_saved_bytes as isize;
+
+    let (cached, candidates): (Vec<_>, Vec<_>) = map
         .into_iter()
-        .filter(|(i, c)| *c > 1 && i.len() > 2)
-        .sorted_by_key(|(_, c)| Reverse(*c))
-        .sorted_by_key(|(v, _)| Reverse(v.len()))
-        .collect::<Vec<_>>()
+        .filter(|(value, occurrences)| calculator.is_candidate(value, occurrences))
+        .sorted()
+        .partition(|(value, _)| allocator.cached(value).is_some());
+
+    let cached = cached
         .into_iter()
-        .partition(|(ident, count)| {
-            VAR_HOSTING.should_compress(
-                constant::COMPRESS_CHARACTER_WIDTH as usize,
-                ident.len(),
-                *count,
-            )
-        });
+        .map(|(value, occurrences)| {
+            let name = allocator.cached(&value).unwrap_or_default();
+            let saved = calculator.saved_bytes(name, &value, &occurrences);
+
+            (value, occurrences, saved)
+        })
+        .filter(|(_, _, saved)| *saved >= min_saved)
+        .collect::<Vec<_>>();
+
+    let mut allocator = allocator.clone();
+    let names = candidates
+        .iter()
+        .map_while(|_| allocator.alloc())
+        .collect::<Vec<_>>();
+
+    // the declaration is already paid by the cached strings
+    let fixed_cost = if cached.is_empty() {
+        DECLARATION_FIXED_COST as isize
+    } else {
+        0
+    };
+
+    let selection = solve_with_threshold(
+        candidates.len(),
+        &names,
+        fixed_cost,
+        min_saved,
+        |i, name| {
+            let (ident, occurrences) = &candidates[i];
 
-    let mut end = v.len();
+            calculator.saved_bytes(name, ident, occurrences)
+        },
+    );
 
-    // TODO: binary search
-    for i in (0..end).rev() {
-        let (ident, count) = &v[i];
+    let cached_saved = cached.iter().map(|(_, _, saved)| saved).sum::<isize>();
 
-        if VAR_HOSTING.should_compress(i, ident.len(), *count) {
-            break;
+    if selection.ranked.is_empty() && cached_saved <= DECLARATION_FIXED_COST as isize {
+        return vec![];
+    }
+
+    cached
+        .into_iter()
+        .map(|(value, occurrences, _)| (value, occurrences))
+        .chain(selection.ranked.into_iter().map(|i| candidates[i].clone()))
+        .collect()
+}
+
+/// The size that matters for the emitted chunk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompressObjective {
+    /// bytes on disk
+    #[default]
+    Raw,
+    /// bytes after `gzip -9`
+    Gzip,
+    /// bytes after `brotli -q 11`
+    Brotli,
+}
+
+impl CompressObjective {
+    pub fn compressed_len(&self, bytes: &[u8]) -> usize {
+        match self {
+            CompressObjective::Raw => bytes.len(),
+            CompressObjective::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+
+                encoder
+                    .write_all(bytes)
+                    .and_then(|_| encoder.finish())
+                    .map_or(bytes.len(), |v| v.len())
+            }
+            CompressObjective::Brotli => {
+                let mut buf = vec![];
+
+                {
+                    let mut writer = brotli::CompressorWriter::new(&mut buf, 4096, 11, 22);
+
+                    if writer.write_all(bytes).is_err() {
+                        return bytes.len();
+                    }
+                }
+
+                buf.len()
+            }
+        }
+    }
+}
+
+///
+/// Measures the real compressed size of the output instead of estimating raw bytes.
+///
+/// gzip/brotli already dedupe repeated strings, so hoisting one can make the compressed
+/// chunk larger even when it saves raw bytes. `render` prints the module with the given
+/// candidates hoisted, the candidates that do not pay off after compression are dropped.
+///
+struct CompressedSize<F> {
+    estimate: HostingVariable,
+    objective: CompressObjective,
+    render: F,
+}
+
+impl<F> CompressedSize<F>
+where
+    F: FnMut(&[(String, Occurrences)]) -> Option<Vec<u8>>,
+{
+    /// a render that fails is never the smallest
+    fn measure(&mut self, selected: &[(String, Occurrences)]) -> usize {
+        (self.render)(selected).map_or(usize::MAX, |code| self.objective.compressed_len(&code))
+    }
+}
+
+impl<F> CostCalculator for CompressedSize<F>
+where
+    F: FnMut(&[(String, Occurrences)]) -> Option<Vec<u8>>,
+{
+    // a candidate that does not save raw bytes will not save compressed bytes either
+    fn declaration_cost(&self, name: &str, value: &str) -> usize {
+        self.estimate.declaration_cost(name, value)
+    }
+
+    fn usage_cost(&self, name: &str, value: &str, kind: OccurrenceKind) -> (usize, usize) {
+        self.estimate.usage_cost(name, value, kind)
+    }
+
+    fn thresholds(&self) -> Thresholds {
+        self.estimate.thresholds()
+    }
+
+    fn select(
+        &mut self,
+        map: FxHashMap<String, Occurrences>,
+        allocator: &TokenAllocator,
+    ) -> Vec<(String, Occurrences)> {
+        let candidates = select_by_estimate(self, map, allocator);
+
+        if matches!(self.objective, CompressObjective::Raw) || candidates.is_empty() {
+            return candidates;
         }
 
-        end = i;
+        prune_greedily(candidates, |selected| self.measure(selected))
     }
+}
 
-    if end != v.len() {
-        v.truncate(end);
+///
+/// Drops the candidates whose removal makes `measure` smaller, the rank of the rest is kept.
+///
+/// It is a best-effort heuristic, not a search for the smallest subset: the candidates that
+/// save the least raw bytes are tried first, in halving groups, and a group is only split
+/// when dropping it as a whole does not help. A drop is never undone, so a candidate only
+/// worth dropping along with one from another group is kept. Removing all of them is the
+/// first trial, so the result is never worse than not hoisting at all.
+///
+fn prune_greedily<M>(
+    candidates: Vec<(String, Occurrences)>,
+    mut measure: M,
+) -> Vec<(String, Occurrences)>
+where
+    M: FnMut(&[(String, Occurrences)]) -> usize,
+{
+    let trials = (0..candidates.len())
+        .sorted_by(|a, b| {
+            let (a, a_occurrences) = &candidates[*a];
+            let (b, b_occurrences) = &candidates[*b];
+
+            (a.len() * a_occurrences.total())
+                .cmp(&(b.len() * b_occurrences.total()))
+                .then_with(|| a.cmp(b))
+        })
+        .collect::<Vec<_>>();
+
+    let mut removed = vec![false; candidates.len()];
+
+    let kept = |removed: &[bool]| {
+        candidates
+            .iter()
+            .zip(removed)
+            .filter(|(_, removed)| !**removed)
+            .map(|(v, _)| v.clone())
+            .collect::<Vec<_>>()
     };
 
-    [v1, v].concat().into_iter().collect()
+    let mut best = measure(&kept(&removed));
+    let mut stack = vec![];
+    stack.push(0..trials.len());
+
+    while let Some(range) = stack.pop() {
+        if range.is_empty() {
+            continue;
+        }
+
+        trials[range.clone()]
+            .iter()
+            .for_each(|i| removed[*i] = true);
+
+        let size = measure(&kept(&removed));
+
+        if size < best {
+            best = size;
+            continue;
+        }
+
+        trials[range.clone()]
+            .iter()
+            .for_each(|i| removed[*i] = false);
+
+        if range.len() > 1 {
+            let mid = range.start + range.len() / 2;
+            // try the cheaper half first
+            stack.push(mid..range.end);
+            stack.push(range.start..mid);
+        }
+    }
+
+    kept(&removed)
+}
+
+///
+/// Returns the strings worth hoisting, ranked by the name they should take from `allocator`.
+///
+/// Assigning the names in this order is what makes the estimated savings hold: the strings
+/// that lose the most bytes per extra name character take the shortest names.
+///
+pub fn filter_cannot_compress_ident(
+    map: FxHashMap<String, Occurrences>,
+    allocator: &TokenAllocator,
+    ascii_only: bool,
+    strategy: &CostStrategy,
+) -> Vec<(String, Occurrences)> {
+    HostingVariable::new(ascii_only, strategy).select(map, allocator)
+}
+
+///
+/// Whether one usage of `value` as `kind` gets shorter when replaced by `name`.
+///
+/// The hoisted string may still have usages that would grow, e.g. `{ foo }`, they are skipped.
+///
+pub fn should_replace_occurrence(
+    name: &str,
+    value: &str,
+    kind: OccurrenceKind,
+    ascii_only: bool,
+) -> bool {
+    HostingVariable::new(ascii_only, &CostStrategy::default()).should_replace(name, value, kind)
+}
+
+///
+/// Same as [`filter_cannot_compress_ident`], but keeps only the candidates that make
+/// the compressed output smaller.
+///
+/// `render` returns the code with the given candidates hoisted, `None` when it cannot be printed.
+///
+pub fn filter_by_compressed_size<F>(
+    map: FxHashMap<String, Occurrences>,
+    allocator: &TokenAllocator,
+    ascii_only: bool,
+    strategy: &CostStrategy,
+    objective: CompressObjective,
+    render: F,
+) -> Vec<(String, Occurrences)>
+where
+    F: FnMut(&[(String, Occurrences)]) -> Option<Vec<u8>>,
+{
+    CompressedSize {
+        estimate: HostingVariable::new(ascii_only, strategy),
+        objective,
+        render,
+    }
+    .select(map, allocator)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    use crate::core::{constant, is_ident_name};
+
+    /// `value` used `count` times, as `.value` when it can be written so
+    fn occurrences(value: &str, count: usize) -> Occurrences {
+        if is_ident_name(value) {
+            Occurrences::new().with(OccurrenceKind::Member, count)
+        } else {
+            Occurrences::new().with(OccurrenceKind::Lit, count)
+        }
+    }
+
+    fn to_occurrences(map: FxHashMap<String, usize>) -> FxHashMap<String, Occurrences> {
+        map.into_iter()
+            .map(|(value, count)| {
+                let occurrences = occurrences(&value, count);
+
+                (value, occurrences)
+            })
+            .collect()
+    }
+
+    fn to_occurrences_vec(v: Vec<(String, usize)>) -> Vec<(String, Occurrences)> {
+        v.into_iter()
+            .map(|(value, count)| {
+                let occurrences = occurrences(&value, count);
+
+                (value, occurrences)
+            })
+            .collect()
+    }
+
+    fn to_counts(v: Vec<(String, Occurrences)>) -> Vec<(String, usize)> {
+        v.into_iter()
+            .map(|(value, occurrences)| (value, occurrences.total()))
+            .collect()
+    }
+
     mod cost {
-        use crate::core::compress_ident::{CostCalculator, VAR_HOSTING};
+        use super::occurrences;
+        use crate::core::compress_ident::{CostCalculator, HostingVariable};
+        use crate::core::occurrence::{OccurrenceKind, Occurrences};
+        use crate::core::strategy::CostPreset;
+
+        const VAR_HOSTING: HostingVariable = HostingVariable {
+            ascii_only: false,
+            thresholds: CostPreset::Balanced.thresholds(),
+        };
 
         #[test]
         fn f1() {
-            let v = VAR_HOSTING.should_compress(0, 3, 1);
+            let v = VAR_HOSTING.should_compress("a", "aaa", &occurrences("aaa", 1));
 
             assert!(!v);
 
-            let v = VAR_HOSTING.should_compress(0, 3, 8);
+            let v = VAR_HOSTING.should_compress("a", "aaa", &occurrences("aaa", 8));
 
             assert!(!v);
 
-            let v = VAR_HOSTING.should_compress(0, 3, 20);
+            let v = VAR_HOSTING.should_compress("a", "aaa", &occurrences("aaa", 20));
             assert!(v);
 
-            let v = VAR_HOSTING.should_compress(0, 3, 100);
+            let v = VAR_HOSTING.should_compress("a", "aaa", &occurrences("aaa", 100));
             assert!(v);
         }
+
+        #[test]
+        fn exact_bytes() {
+            // a="foo",
+            assert_eq!(VAR_HOSTING.declaration_cost("a", "foo"), 8);
+            // .foo => [a]
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "foo", OccurrenceKind::Member),
+                (4, 3)
+            );
+            // "./foo" => a
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "./foo", OccurrenceKind::Lit),
+                (7, 1)
+            );
+            // .foo => [ab]
+            assert_eq!(
+                VAR_HOSTING.saved_bytes("ab", "foo", &occurrences("foo", 20)),
+                -9
+            );
+        }
+
+        #[test]
+        fn quote_and_unicode() {
+            assert_eq!(VAR_HOSTING.declaration_cost("a", "it's \"ok\""), 15);
+
+            let ascii = HostingVariable {
+                ascii_only: true,
+                ..VAR_HOSTING
+            };
+
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "中文", OccurrenceKind::Member),
+                (7, 3)
+            );
+            assert_eq!(
+                ascii.usage_cost("a", "中文", OccurrenceKind::Member),
+                (13, 3)
+            );
+            assert!(!VAR_HOSTING.should_compress("a", "中文", &occurrences("中文", 2)));
+            assert!(ascii.should_compress("a", "中文", &occurrences("中文", 2)));
+        }
+
+        #[test]
+        fn occurrence_kinds() {
+            // foo: => [a]:
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "foo", OccurrenceKind::Key),
+                (3, 3)
+            );
+            // "./foo": => [a]:
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "./foo", OccurrenceKind::LitKey),
+                (7, 3)
+            );
+            // { foo } => { [a]: foo }
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "foo", OccurrenceKind::Shorthand),
+                (3, 7)
+            );
+
+            // `foo${x}` => `${a}${x}`
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "foo", OccurrenceKind::Quasi),
+                (3, 4)
+            );
+            // "./foo/bar" => a+"bar"
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "./foo/", OccurrenceKind::Prefix),
+                (6, 2)
+            );
+
+            // <div> => <A>
+            assert_eq!(
+                VAR_HOSTING.usage_cost("A", "div", OccurrenceKind::JsxElement),
+                (3, 1)
+            );
+
+            assert!(VAR_HOSTING.should_replace("a", "foo", OccurrenceKind::Member));
+            assert!(!VAR_HOSTING.should_replace("a", "div", OccurrenceKind::JsxElement));
+            assert!(!VAR_HOSTING.should_replace("a", "foo", OccurrenceKind::Key));
+            assert!(!VAR_HOSTING.should_replace("a", "foo", OccurrenceKind::Shorthand));
+        }
+
+        #[test]
+        fn growing_usages_are_not_counted() {
+            let members = Occurrences::new().with(OccurrenceKind::Member, 20);
+            let with_shorthand = members.clone().with(OccurrenceKind::Shorthand, 20);
+
+            assert_eq!(
+                VAR_HOSTING.saved_bytes("a", "foobar", &members),
+                VAR_HOSTING.saved_bytes("a", "foobar", &with_shorthand)
+            );
+
+            // only keys, `foo:` => `[a]:` saves nothing
+            let keys = Occurrences::new().with(OccurrenceKind::Key, 100);
+
+            assert!(!VAR_HOSTING.should_compress("a", "foo", &keys));
+        }
+
+        #[test]
+        fn constants() {
+            // 4294967295 => a
+            assert_eq!(
+                VAR_HOSTING.usage_cost("a", "4294967295", OccurrenceKind::Constant),
+                (10, 1)
+            );
+
+            // a=4294967295, without quotes
+            let constant = Occurrences::new().with(OccurrenceKind::Constant, 2);
+
+            assert_eq!(
+                VAR_HOSTING.saved_bytes("a", "4294967295", &constant),
+                2 * 9 - 13
+            );
+            assert!(!VAR_HOSTING.should_compress("a", "255", &constant));
+        }
     }
 
     mod filter_compress {
         use super::*;
 
+        fn filter_cannot_compress_ident(map: FxHashMap<String, usize>) -> FxHashMap<String, usize> {
+            to_counts(super::filter_cannot_compress_ident(
+                to_occurrences(map),
+                &TokenAllocator::new(),
+                false,
+                &CostStrategy::default(),
+            ))
+            .into_iter()
+            .collect()
+        }
+
         #[test]
         fn cannot_compress() {
             let map = FxHashMap::from_iter([
@@ -187,21 +725,283 @@ mod tests {
                 ("aaa".to_string(), 10),
             ]);
 
+            let v = filter_cannot_compress_ident(map.clone());
+
+            // `aaa` and `aa9` save 3 bytes, not enough for `var ;`
+            assert_eq!(v, FxHashMap::default());
+
+            let mut map = map;
+            map.insert("aab".to_string(), 20);
+
             let v = filter_cannot_compress_ident(map);
 
             assert_eq!(
                 v,
-                FxHashMap::from_iter([("aaa".to_string(), 10), ("aa9".to_string(), 9)])
+                FxHashMap::from_iter([
+                    ("aab".to_string(), 20),
+                    ("aaa".to_string(), 10),
+                    ("aa9".to_string(), 9)
+                ])
             );
         }
 
         #[test]
         fn t3() {
+            // var a="localStorage";x[a];x[a] is longer than x.localStorage;x.localStorage
             let map = FxHashMap::from_iter([("localStorage".to_string(), 2)]);
 
+            let v = filter_cannot_compress_ident(map);
+
+            assert_eq!(v, FxHashMap::default());
+
+            let map = FxHashMap::from_iter([("localStorage".to_string(), 3)]);
+
+            let v = filter_cannot_compress_ident(map.clone());
+
+            assert_eq!(v, map);
+        }
+
+        #[test]
+        fn use_allocated_name() {
+            let map = FxHashMap::from_iter([("aaaa".to_string(), 13)]);
+
             let v = filter_cannot_compress_ident(map.clone());
 
             assert_eq!(v, map);
+
+            // all letters are used, `$` is still one character
+            let mut allocator = TokenAllocator::new();
+            allocator.extends(
+                (b'a'..=b'z')
+                    .chain(b'A'..=b'Z')
+                    .map(|ch| (ch as char).to_string())
+                    .collect(),
+            );
+
+            let v = super::filter_cannot_compress_ident(
+                to_occurrences(map.clone()),
+                &allocator,
+                false,
+                &CostStrategy::default(),
+            );
+
+            assert_eq!(to_counts(v), Vec::from_iter(map.clone()));
+
+            // all single character names are used, `.aaaa` => `[aa]` only saves 1 byte
+            let mut allocator = TokenAllocator::new();
+            allocator.extends(
+                constant::LEADING_CHARACTERS
+                    .iter()
+                    .map(|ch| (*ch as char).to_string())
+                    .collect(),
+            );
+
+            let v = super::filter_cannot_compress_ident(
+                to_occurrences(map),
+                &allocator,
+                false,
+                &CostStrategy::default(),
+            );
+
+            assert_eq!(v, vec![]);
+        }
+
+        #[test]
+        fn strategy() {
+            let select = |map: FxHashMap<String, usize>, strategy: CostStrategy| {
+                to_counts(super::filter_cannot_compress_ident(
+                    to_occurrences(map),
+                    &TokenAllocator::new(),
+                    false,
+                    &strategy,
+                ))
+            };
+
+            // "./" => a saves 3 bytes per usage
+            let map = FxHashMap::from_iter([("./".to_string(), 10)]);
+
+            assert_eq!(select(map.clone(), CostPreset::Balanced.into()), vec![]);
+            assert_eq!(
+                select(map, CostPreset::Aggressive.into()),
+                vec![("./".to_string(), 10)]
+            );
+
+            // .foobar => [a] saves 4 bytes per usage, 9 bytes in total
+            let map = FxHashMap::from_iter([("foobar".to_string(), 5)]);
+
+            assert_eq!(
+                select(map.clone(), CostPreset::Balanced.into()),
+                vec![("foobar".to_string(), 5)]
+            );
+            assert_eq!(select(map.clone(), CostPreset::Conservative.into()), vec![]);
+            assert_eq!(
+                select(
+                    map.clone(),
+                    CostStrategy {
+                        preset: CostPreset::Conservative,
+                        min_saved_bytes: Some(9),
+                        ..Default::default()
+                    }
+                ),
+                vec![("foobar".to_string(), 5)]
+            );
+            assert_eq!(
+                select(
+                    map,
+                    CostStrategy {
+                        min_occurrences: Some(6),
+                        ..Default::default()
+                    }
+                ),
+                vec![]
+            );
+        }
+
+        #[test]
+        fn price_cached_names() {
+            let select = |cache: [(String, String); 1]| {
+                to_counts(super::filter_cannot_compress_ident(
+                    to_occurrences(FxHashMap::from_iter([
+                        ("localStorage".to_string(), 3),
+                        ("sessionStorage".to_string(), 3),
+                    ])),
+                    &TokenAllocator::new().with_cache(cache),
+                    false,
+                    &CostStrategy::default(),
+                ))
+            };
+
+            // `.localStorage` => `[abcdefghijkl]` saves nothing
+            let v = select([("localStorage".to_string(), "abcdefghijkl".to_string())]);
+
+            assert_eq!(v, vec![("sessionStorage".to_string(), 3)]);
+
+            // the cached name comes first, whatever its length
+            let v = select([("localStorage".to_string(), "ab".to_string())]);
+
+            assert_eq!(
+                v,
+                vec![
+                    ("localStorage".to_string(), 3),
+                    ("sessionStorage".to_string(), 3)
+                ]
+            );
+        }
+
+        #[test]
+        fn rank_by_saved_bytes_per_name_character() {
+            let map = FxHashMap::from_iter([
+                ("a".repeat(40), 3),
+                ("bbb".to_string(), 300),
+                ("cccc".to_string(), 20),
+            ]);
+
+            let v = super::filter_cannot_compress_ident(
+                to_occurrences(map),
+                &TokenAllocator::new(),
+                false,
+                &CostStrategy::default(),
+            );
+
+            assert_eq!(
+                to_counts(v),
+                vec![
+                    ("bbb".to_string(), 300),
+                    ("cccc".to_string(), 20),
+                    ("a".repeat(40), 3)
+                ]
+            );
+        }
+    }
+
+    mod compressed {
+        use super::*;
+
+        #[test]
+        fn compressed_len() {
+            let code = "console.log(\"hello world\");".repeat(100);
+
+            assert_eq!(
+                CompressObjective::Raw.compressed_len(code.as_bytes()),
+                code.len()
+            );
+            assert!(CompressObjective::Gzip.compressed_len(code.as_bytes()) < code.len());
+            assert!(CompressObjective::Brotli.compressed_len(code.as_bytes()) < code.len());
+        }
+
+        #[test]
+        fn prune_keeps_only_helpful_candidates() {
+            let map = vec![
+                ("good1".to_string(), 10),
+                ("bad1".to_string(), 10),
+                ("good2".to_string(), 10),
+                ("bad2".to_string(), 10),
+            ];
+
+            let v = prune_greedily(to_occurrences_vec(map), |selected| {
+                selected.iter().fold(100, |size, (key, _)| {
+                    if key.starts_with("good") {
+                        size - 10
+                    } else {
+                        size + 3
+                    }
+                })
+            });
+
+            // the rank is kept
+            assert_eq!(
+                to_counts(v),
+                vec![("good1".to_string(), 10), ("good2".to_string(), 10)]
+            );
+        }
+
+        #[test]
+        fn prune_all_when_hoisting_grows() {
+            let map = vec![("foo".to_string(), 10), ("bar".to_string(), 10)];
+
+            let v = prune_greedily(to_occurrences_vec(map), |selected| 100 + selected.len());
+
+            assert_eq!(v, vec![]);
+        }
+
+        #[test]
+        fn raw_objective_does_not_render() {
+            let map = FxHashMap::from_iter([("localStorage".to_string(), 3)]);
+
+            let v = filter_by_compressed_size(
+                to_occurrences(map.clone()),
+                &TokenAllocator::new(),
+                false,
+                &CostStrategy::default(),
+                CompressObjective::Raw,
+                |_| unreachable!(),
+            );
+
+            assert_eq!(to_counts(v), Vec::from_iter(map));
+        }
+
+        #[test]
+        fn gzip_drops_hoisting_that_grows() {
+            let map = FxHashMap::from_iter([("localStorage".to_string(), 3)]);
+
+            let v = filter_by_compressed_size(
+                to_occurrences(map),
+                &TokenAllocator::new(),
+                false,
+                &CostStrategy::default(),
+                CompressObjective::Gzip,
+                |selected| {
+                    let code: &[u8] = if selected.is_empty() {
+                        b"window.localStorage;window.localStorage;window.localStorage;"
+                    } else {
+                        b"var a=\"localStorage\";window[a];window[a];window[a];"
+                    };
+
+                    Some(code.to_vec())
+                },
+            );
+
+            assert_eq!(v, vec![]);
         }
     }
 }
@@ -1 +1,4 @@
-pub const COMPRESS_CHARACTER_WIDTH: u8 = 52;
\ No newline at end of file
+pub const LEADING_CHARACTERS: &[u8; 54] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ$_";
+
+pub const TRAILING_CHARACTERS: &[u8; 64] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ$_0123456789";
@@ -1,6 +1,26 @@
+mod alias;
+mod budget;
+mod compose;
 mod compress_ident;
+mod occurrence;
+mod printed_len;
+mod reserved;
+mod selection;
+mod shape;
+mod strategy;
 mod token_allocator;
 
-pub use compress_ident::filter_cannot_compress_ident;
+pub use alias::{select_aliases, select_local_alias, ChainAlias};
+pub use budget::fit_size_budget;
+pub use compose::{find_affixes, Affix};
+pub use compress_ident::{
+    filter_by_compressed_size, filter_cannot_compress_ident, should_replace_occurrence,
+    CompressObjective, CostCalculator, HostingVariable,
+};
+pub use occurrence::{OccurrenceKind, Occurrences};
+pub use printed_len::{ident_len, is_ident_name, quoted_len};
+pub use reserved::{EnvProfile, ReservedNames, ReservedOption};
+pub use shape::{select_shapes, ObjectShape};
+pub use strategy::{CostPreset, CostStrategy, Thresholds};
 pub use token_allocator::TokenAllocator;
 pub mod constant;
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+///
+/// Where a string appears, each kind is printed differently once it is replaced.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OccurrenceKind {
+    /// `obj.foo` => `obj[a]`
+    Member,
+    /// `"foo"` => `a`, `obj["foo"]` => `obj[a]`
+    Lit,
+    /// `{ foo: 1 }` => `{ [a]: 1 }`
+    Key,
+    /// `{ "foo": 1 }` => `{ [a]: 1 }`
+    LitKey,
+    /// `{ foo }` => `{ [a]: foo }`
+    Shorthand,
+    /// `` `foo${x}` `` => `` `${a}${x}` ``
+    Quasi,
+    /// `"foo/bar"` => `a+"bar"`, see [`crate::find_affixes`]
+    Prefix,
+    /// `"bar/foo"` => `"bar/"+a`, see [`crate::find_affixes`]
+    Suffix,
+    /// `<div title="foo">` => `<div title={a}>`
+    JsxAttr,
+    /// `<div>` => `<A>`, only with a name that is not an intrinsic element
+    JsxElement,
+    /// `4294967295`, `10n` or `/foo/i` => `a`, the value is the constant as it is written
+    Constant,
+}
+
+impl OccurrenceKind {
+    pub const ALL: [OccurrenceKind; 11] = [
+        OccurrenceKind::Member,
+        OccurrenceKind::Lit,
+        OccurrenceKind::Key,
+        OccurrenceKind::LitKey,
+        OccurrenceKind::Shorthand,
+        OccurrenceKind::Quasi,
+        OccurrenceKind::Prefix,
+        OccurrenceKind::Suffix,
+        OccurrenceKind::JsxAttr,
+        OccurrenceKind::JsxElement,
+        OccurrenceKind::Constant,
+    ];
+}
+
+/// How many times a string appears as each [`OccurrenceKind`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Occurrences {
+    counts: [usize; OccurrenceKind::ALL.len()],
+}
+
+impl Occurrences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, kind: OccurrenceKind, count: usize) -> Self {
+        self.counts[kind as usize] += count;
+        self
+    }
+
+    pub fn add(&mut self, kind: OccurrenceKind) {
+        self.counts[kind as usize] += 1;
+    }
+
+    pub fn count(&self, kind: OccurrenceKind) -> usize {
+        self.counts[kind as usize]
+    }
+
+    /// whether the value is a constant and not a string, see [`OccurrenceKind::Constant`]
+    pub fn is_constant(&self) -> bool {
+        self.count(OccurrenceKind::Constant) > 0
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (OccurrenceKind, usize)> + '_ {
+        OccurrenceKind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.count(kind)))
+            .filter(|(_, count)| *count > 0)
+    }
+}
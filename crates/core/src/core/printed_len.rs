@@ -0,0 +1,107 @@
+//!
+//! Byte length of tokens as printed by `swc_ecma_codegen` with the default target (es5).
+//!
+
+/// `ch` as printed inside a string literal, quotes excluded.
+fn str_char_len(ch: char, next: Option<char>, ascii_only: bool) -> usize {
+    match ch {
+        '\x00' if matches!(next, Some('0'..='9')) => 4,
+        '\x00' | '\u{0008}' | '\u{000c}' | '\n' | '\r' | '\u{000b}' | '\\' => 2,
+        '\x01'..='\x1f' if ch != '\t' => 4,
+        // es5 prints latin-1 as `\xNN`
+        '\u{7f}'..='\u{ff}' => 4,
+        '\u{2028}' | '\u{2029}' | '\u{FEFF}' => 6,
+        // surrogate pairs
+        _ if ch > '\u{FFFF}' => 12,
+        _ if !ch.is_ascii() && ascii_only => 6,
+        _ => ch.len_utf8(),
+    }
+}
+
+///
+/// length of `value` printed as a string literal, quotes included.
+///
+/// ```unknown
+/// foo'bar => "foo'bar"
+/// foo"bar => 'foo"bar'
+/// ```
+///
+pub fn quoted_len(value: &str, ascii_only: bool) -> usize {
+    let mut single_quote_count = 0;
+    let mut double_quote_count = 0;
+    let mut len = 0;
+    let mut chars = value.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => single_quote_count += 1,
+            '"' => double_quote_count += 1,
+            _ => {}
+        }
+
+        len += str_char_len(ch, chars.peek().copied(), ascii_only);
+    }
+
+    // the quote that appears less is used, and the other one is escaped
+    len + single_quote_count.min(double_quote_count) + 2
+}
+
+/// length of `value` printed as an identifier or a property name.
+pub fn ident_len(value: &str, ascii_only: bool) -> usize {
+    value
+        .chars()
+        .map(|ch| match ch {
+            _ if ch.is_ascii() || !ascii_only => ch.len_utf8(),
+            _ if ch > '\u{FFFF}' => 12,
+            _ => 6,
+        })
+        .sum()
+}
+
+/// whether `value` can be written as `obj.value` or `{ value: 1 }`.
+pub fn is_ident_name(value: &str) -> bool {
+    let mut chars = value.chars();
+
+    let Some(first) = chars.next() else {
+        return false;
+    };
+
+    (first.is_alphabetic() || first == '_' || first == '$')
+        && chars.all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted() {
+        assert_eq!(quoted_len("foo", false), 5);
+        assert_eq!(quoted_len("foo'bar", false), 9);
+        assert_eq!(quoted_len("foo'\"bar", false), 11);
+        assert_eq!(quoted_len("a\nb", false), 6);
+        assert_eq!(quoted_len("a\\b", false), 6);
+        assert_eq!(quoted_len("\x000", false), 7);
+    }
+
+    #[test]
+    fn quoted_non_ascii() {
+        assert_eq!(quoted_len("中文", false), 8);
+        assert_eq!(quoted_len("中文", true), 14);
+        assert_eq!(quoted_len("é", false), 6);
+        assert_eq!(quoted_len("😀", false), 14);
+    }
+
+    #[test]
+    fn ident() {
+        assert_eq!(ident_len("foo", false), 3);
+        assert_eq!(ident_len("中文", false), 6);
+        assert_eq!(ident_len("中文", true), 12);
+
+        assert!(is_ident_name("$foo_1"));
+        assert!(is_ident_name("中文"));
+        assert!(!is_ident_name("1foo"));
+        assert!(!is_ident_name("./foo"));
+        assert!(!is_ident_name(""));
+    }
+}
@@ -0,0 +1,312 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    ///
+    /// Names a variable can never take, in sloppy and strict mode.
+    ///
+    static ref RESERVED_WORDS: FxHashSet<&'static str> = {
+        FxHashSet::from_iter([
+            // keywords
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "debugger",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "export",
+            "extends",
+            "finally",
+            "for",
+            "function",
+            "if",
+            "import",
+            "in",
+            "instanceof",
+            "new",
+            "return",
+            "super",
+            "switch",
+            "this",
+            "throw",
+            "try",
+            "typeof",
+            "var",
+            "void",
+            "while",
+            "with",
+            // literals
+            "false",
+            "null",
+            "true",
+            // future reserved, strict mode included
+            "await",
+            "enum",
+            "implements",
+            "interface",
+            "let",
+            "package",
+            "private",
+            "protected",
+            "public",
+            "static",
+            "yield",
+            // can not be declared in strict mode, or are read-only globals
+            "arguments",
+            "eval",
+            "undefined",
+            "NaN",
+            "Infinity",
+        ])
+    };
+}
+
+/// globals of the libraries commonly loaded with a `<script>`
+const LIBRARY_GLOBALS: &[&str] = &["$", "_", "jQuery"];
+
+const BROWSER_GLOBALS: &[&str] = &[
+    "window",
+    "self",
+    "top",
+    "parent",
+    "frames",
+    "opener",
+    "name",
+    "length",
+    "status",
+    "closed",
+    "origin",
+    "event",
+    "external",
+    "document",
+    "location",
+    "history",
+    "navigator",
+    "screen",
+    "performance",
+    "crypto",
+    "caches",
+    "indexedDB",
+    "localStorage",
+    "sessionStorage",
+    "console",
+    "alert",
+    "confirm",
+    "prompt",
+    "open",
+    "close",
+    "stop",
+    "focus",
+    "blur",
+    "print",
+    "find",
+    "fetch",
+    "postMessage",
+    "globalThis",
+];
+
+const WORKER_GLOBALS: &[&str] = &[
+    "self",
+    "name",
+    "location",
+    "navigator",
+    "performance",
+    "crypto",
+    "caches",
+    "indexedDB",
+    "console",
+    "close",
+    "fetch",
+    "importScripts",
+    "postMessage",
+    "onmessage",
+    "onerror",
+    "globalThis",
+];
+
+const NODE_GLOBALS: &[&str] = &[
+    "require",
+    "module",
+    "exports",
+    "__filename",
+    "__dirname",
+    "global",
+    "process",
+    "Buffer",
+    "console",
+    "globalThis",
+];
+
+const MINI_PROGRAM_GLOBALS: &[&str] = &[
+    "App",
+    "Page",
+    "Component",
+    "Behavior",
+    "getApp",
+    "getCurrentPages",
+    "requirePlugin",
+    "require",
+    "module",
+    "exports",
+    "global",
+    "console",
+    "globalThis",
+    // the api namespace of each platform
+    "wx",
+    "my",
+    "tt",
+    "qq",
+    "swan",
+    "jd",
+    "ks",
+    "dd",
+    "uni",
+];
+
+/// The environment running the output, its globals are never taken by a hoisted variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EnvProfile {
+    /// `window`, `name`, `top`, `self`, `event`, ... and `$`, `_`
+    Browser,
+    /// `self`, `name`, `importScripts`, ... and `$`, `_`
+    Worker,
+    /// `require`, `module`, `exports`, `process`, ...
+    Node,
+    /// `wx`, `App`, `Page`, `getApp`, ... and `$`, `_`
+    MiniProgram,
+}
+
+impl EnvProfile {
+    pub fn globals(&self) -> impl Iterator<Item = &'static str> {
+        let (globals, libraries) = match self {
+            EnvProfile::Browser => (BROWSER_GLOBALS, LIBRARY_GLOBALS),
+            EnvProfile::Worker => (WORKER_GLOBALS, LIBRARY_GLOBALS),
+            EnvProfile::Node => (NODE_GLOBALS, &[][..]),
+            EnvProfile::MiniProgram => (MINI_PROGRAM_GLOBALS, LIBRARY_GLOBALS),
+        };
+
+        globals.iter().chain(libraries).copied()
+    }
+}
+
+///
+/// The names a hoisted variable must not take, besides the reserved words.
+///
+/// ```json
+/// { "profiles": ["browser"], "names": ["myGlobal"], "patterns": ["^__"] }
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReservedOption {
+    pub profiles: Vec<EnvProfile>,
+    pub names: Vec<String>,
+    /// regular expressions, a name matching any of them is reserved
+    pub patterns: Vec<String>,
+}
+
+impl ReservedOption {
+    pub fn compile(&self) -> Result<ReservedNames, regex::Error> {
+        let names = self
+            .profiles
+            .iter()
+            .flat_map(|profile| profile.globals())
+            .map(|name| name.to_string())
+            .chain(self.names.iter().cloned())
+            .collect();
+
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<_, _>>()?;
+
+        Ok(ReservedNames { names, patterns })
+    }
+}
+
+/// The compiled [`ReservedOption`].
+#[derive(Debug, Default, Clone)]
+pub struct ReservedNames {
+    names: FxHashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+impl ReservedNames {
+    pub fn contains(&self, name: &str) -> bool {
+        RESERVED_WORDS.contains(name)
+            || self.names.contains(name)
+            || self.patterns.iter().any(|pattern| pattern.is_match(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_words() {
+        let reserved = ReservedNames::default();
+
+        assert!(reserved.contains("if"));
+        assert!(reserved.contains("let"));
+        assert!(reserved.contains("undefined"));
+        // not reserved in javascript
+        assert!(!reserved.contains("as"));
+        assert!(!reserved.contains("any"));
+        assert!(!reserved.contains("declare"));
+        assert!(!reserved.contains("name"));
+    }
+
+    #[test]
+    fn profiles() -> Result<(), regex::Error> {
+        let reserved = ReservedOption {
+            profiles: vec![EnvProfile::Browser, EnvProfile::Node],
+            ..Default::default()
+        }
+        .compile()?;
+
+        for name in [
+            "name", "top", "self", "event", "$", "_", "require", "process",
+        ] {
+            assert!(reserved.contains(name), "{}", name);
+        }
+
+        assert!(!reserved.contains("wx"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn names_and_patterns() -> Result<(), regex::Error> {
+        let option = ReservedOption {
+            names: vec!["foo".to_string()],
+            patterns: vec!["^_".to_string(), "^[A-Z]$".to_string()],
+            ..Default::default()
+        };
+        let reserved = option.compile()?;
+
+        assert!(reserved.contains("foo"));
+        assert!(reserved.contains("_a"));
+        assert!(reserved.contains("Q"));
+        assert!(!reserved.contains("QQ"));
+        assert!(!reserved.contains("a_"));
+
+        let option = ReservedOption {
+            patterns: vec!["(".to_string()],
+            ..Default::default()
+        };
+
+        assert!(option.compile().is_err());
+
+        Ok(())
+    }
+}
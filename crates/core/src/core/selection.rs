@@ -0,0 +1,490 @@
+use itertools::Itertools;
+
+use super::{compress_ident::DECLARATION_FIXED_COST, CostStrategy, TokenAllocator};
+
+/// at most this many rounds are run before the best solution seen so far is returned
+const MAX_ROUNDS: usize = 8;
+
+/// above this many candidates, the quadratic dynamic programming gives way to a greedy pass
+const MAX_DP_CANDIDATES: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// index of the selected candidates, the i-th one takes `names[i]`
+    pub ranked: Vec<usize>,
+    pub saved: isize,
+}
+
+impl Selection {
+    fn empty() -> Self {
+        Self {
+            ranked: vec![],
+            saved: 0,
+        }
+    }
+}
+
+///
+/// Decides which candidates are hoisted and which name each of them takes, a heuristic.
+///
+/// `saved(candidate, name)` is the number of bytes saved when `candidate` is hoisted as `name`,
+/// declaration included. `names` are the names that will be handed out, in order, and
+/// `fixed_cost` is paid once when anything is hoisted.
+///
+/// The candidates are put in an order, the ones that lose the most bytes per extra name
+/// character first, and the best subset taking the names in that order is found by dynamic
+/// programming over the number of selected candidates. The order is recomputed with the
+/// names each candidate got, for a few rounds. When each candidate loses a fixed number of
+/// bytes per name character the first order is already the best one, so nothing beats the
+/// result; otherwise, e.g. when a usage is only rewritten with a short enough name, a better
+/// subset may exist.
+///
+/// The order is priced with probe names of each length. `saved` may also depend on the case
+/// of the name, `<a>` is an element but `<A>` is not, so a probe is the better of a lowercase
+/// and an uppercase name. The subsets are priced with `names` themselves.
+///
+/// Past [`MAX_DP_CANDIDATES`], a candidate is taken in order when it saves bytes with the
+/// next name, see [`solve_greedy`].
+///
+pub fn solve<F>(count: usize, names: &[String], fixed_cost: isize, saved: F) -> Selection
+where
+    F: Fn(usize, &str) -> isize,
+{
+    let shortest = names.first().map_or(1, |name| name.len());
+    let probed =
+        |i: usize, len: usize| saved(i, &probe(len, false)).max(saved(i, &probe(len, true)));
+
+    // a candidate that saves nothing with the shortest name can not help
+    let candidates = (0..count)
+        .filter(|i| probed(*i, shortest) > 0)
+        .collect::<Vec<_>>();
+
+    let mut lens = vec![shortest; count];
+    let mut best = Selection::empty();
+    let mut prev_order = None;
+
+    for _ in 0..MAX_ROUNDS {
+        let order = candidates
+            .iter()
+            .copied()
+            .sorted_by(|a, b| {
+                let penalty = |i: usize| probed(i, lens[i]) - probed(i, lens[i] + 1);
+
+                penalty(*b)
+                    .cmp(&penalty(*a))
+                    .then_with(|| probed(*b, lens[*b]).cmp(&probed(*a, lens[*a])))
+                    .then(a.cmp(b))
+            })
+            .collect::<Vec<_>>();
+
+        if prev_order.as_ref() == Some(&order) {
+            break;
+        }
+
+        let selection = solve_ordered(&order, names, fixed_cost, &saved);
+
+        for (rank, i) in selection.ranked.iter().enumerate() {
+            lens[*i] = names[rank].len();
+        }
+
+        if selection.saved > best.saved {
+            best = selection;
+        }
+
+        prev_order = Some(order);
+    }
+
+    best
+}
+
+///
+/// Same as [`solve`], but every selected candidate saves at least `min_saved` bytes with the
+/// name it takes.
+///
+/// The candidates below `min_saved` with the shortest name are dropped before solving. A
+/// candidate that falls below it with a longer name is dropped too, and the rest are solved
+/// again.
+///
+pub(super) fn solve_with_threshold<F>(
+    count: usize,
+    names: &[String],
+    fixed_cost: isize,
+    min_saved: isize,
+    saved: F,
+) -> Selection
+where
+    F: Fn(usize, &str) -> isize,
+{
+    let Some(shortest) = names.first() else {
+        return Selection::empty();
+    };
+
+    let mut candidates = (0..count)
+        .filter(|i| saved(*i, shortest) >= min_saved)
+        .collect::<Vec<_>>();
+
+    loop {
+        let selection = solve(candidates.len(), names, fixed_cost, |i, name| {
+            saved(candidates[i], name)
+        });
+
+        let below = selection
+            .ranked
+            .iter()
+            .zip(names)
+            .filter(|(i, name)| saved(candidates[**i], name) < min_saved)
+            .map(|(i, _)| candidates[*i])
+            .collect::<Vec<_>>();
+
+        if below.is_empty() {
+            return Selection {
+                ranked: selection
+                    .ranked
+                    .into_iter()
+                    .map(|i| candidates[i])
+                    .collect(),
+                saved: selection.saved,
+            };
+        }
+
+        candidates.retain(|i| !below.contains(i));
+    }
+}
+
+///
+/// Selects the candidates declared by a hoisted variable each, like [`solve`], and returns
+/// them with their names taken from `allocator` in order.
+///
+/// `candidates` are used `count` times each, `saved(candidate, name, count)` includes the
+/// declaration.
+///
+pub(super) fn select_counted<T, F>(
+    candidates: Vec<(T, usize)>,
+    allocator: &TokenAllocator,
+    strategy: &CostStrategy,
+    saved: F,
+) -> Vec<(T, String)>
+where
+    T: Clone + Ord,
+    F: Fn(&T, &str, usize) -> isize,
+{
+    let thresholds = strategy.thresholds();
+    let candidates = candidates
+        .into_iter()
+        .filter(|(_, count)| *count >= thresholds.min_occurrences)
+        .sorted()
+        .collect::<Vec<_>>();
+
+    let mut allocator = allocator.clone();
+    let names = candidates
+        .iter()
+        .map_while(|_| allocator.alloc())
+        .collect::<Vec<_>>();
+
+    let selection = solve_with_threshold(
+        candidates.len(),
+        &names,
+        DECLARATION_FIXED_COST as isize,
+        thresholds.min_saved_bytes as isize,
+        |i, name| {
+            let (candidate, count) = &candidates[i];
+
+            saved(candidate, name, *count)
+        },
+    );
+
+    selection
+        .ranked
+        .into_iter()
+        .zip(names)
+        .map(|(i, name)| (candidates[i].0.clone(), name))
+        .collect()
+}
+
+/// a name of `len` characters, `aaa` or `AAA`
+fn probe(len: usize, uppercase: bool) -> String {
+    if uppercase { "A" } else { "a" }.repeat(len)
+}
+
+///
+/// Takes the candidates in `order`, each one that saves bytes with the next name takes it.
+///
+/// Linear in the number of candidates, unlike [`solve_ordered`], but a candidate saving a
+/// few bytes may push the next ones to longer names.
+///
+fn solve_greedy<F>(order: &[usize], names: &[String], fixed_cost: isize, saved: &F) -> Selection
+where
+    F: Fn(usize, &str) -> isize,
+{
+    let mut ranked = vec![];
+    let mut total = -fixed_cost;
+
+    for candidate in order {
+        let Some(name) = names.get(ranked.len()) else {
+            break;
+        };
+
+        let v = saved(*candidate, name);
+
+        if v > 0 {
+            ranked.push(*candidate);
+            total += v;
+        }
+    }
+
+    if ranked.is_empty() || total <= 0 {
+        return Selection::empty();
+    }
+
+    Selection {
+        ranked,
+        saved: total,
+    }
+}
+
+fn solve_ordered<F>(order: &[usize], names: &[String], fixed_cost: isize, saved: &F) -> Selection
+where
+    F: Fn(usize, &str) -> isize,
+{
+    // n * (m + 1) bits are kept to rebuild the subset
+    if order.len() > MAX_DP_CANDIDATES {
+        return solve_greedy(order, names, fixed_cost, saved);
+    }
+
+    let n = order.len();
+    // a candidate left out frees its name for the next ones, so all of them are tried
+    let m = n.min(names.len());
+
+    if m == 0 {
+        return Selection::empty();
+    }
+
+    // dp[k]: the most bytes saved with k candidates selected so far
+    let mut dp = vec![None::<isize>; m + 1];
+    // bit `i * (m + 1) + k`: dp[k] comes from taking order[i] as the k-th candidate
+    let mut taken = vec![0u64; (n * (m + 1)).div_ceil(64)];
+    let bit = |i: usize, k: usize| (i * (m + 1) + k) / 64;
+    let mask = |i: usize, k: usize| 1u64 << ((i * (m + 1) + k) % 64);
+
+    dp[0] = Some(0);
+
+    for (i, candidate) in order.iter().enumerate() {
+        for k in (0..=i.min(m - 1)).rev() {
+            let Some(prev) = dp[k] else {
+                continue;
+            };
+
+            let v = prev + saved(*candidate, &names[k]);
+
+            if dp[k + 1].map_or(true, |cur| v > cur) {
+                dp[k + 1] = Some(v);
+                taken[bit(i, k + 1)] |= mask(i, k + 1);
+            }
+        }
+    }
+
+    let Some((mut k, total)) = dp
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(k, v)| v.map(|v| (k, v - fixed_cost)))
+        // the smaller set wins on ties
+        .max_by(|(a_k, a), (b_k, b)| a.cmp(b).then(b_k.cmp(a_k)))
+    else {
+        return Selection::empty();
+    };
+
+    if total <= 0 {
+        return Selection::empty();
+    }
+
+    let mut ranked = vec![];
+
+    for i in (0..n).rev() {
+        if k == 0 {
+            break;
+        }
+
+        if taken[bit(i, k)] & mask(i, k) != 0 {
+            ranked.push(order[i]);
+            k -= 1;
+        }
+    }
+
+    ranked.reverse();
+
+    Selection {
+        ranked,
+        saved: total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (base, weight): base - weight * name.len()
+    fn linear(items: &[(isize, isize)]) -> impl Fn(usize, &str) -> isize + '_ {
+        |i, name| items[i].0 - items[i].1 * name.len() as isize
+    }
+
+    fn names(lens: &[usize]) -> Vec<String> {
+        lens.iter().map(|len| probe(*len, false)).collect()
+    }
+
+    fn total(
+        items: &[(isize, isize)],
+        names: &[String],
+        fixed_cost: isize,
+        ranked: &[usize],
+    ) -> isize {
+        if ranked.is_empty() {
+            return 0;
+        }
+
+        let saved = linear(items);
+
+        ranked
+            .iter()
+            .enumerate()
+            .map(|(rank, i)| saved(*i, &names[rank]))
+            .sum::<isize>()
+            - fixed_cost
+    }
+
+    fn brute_force(items: &[(isize, isize)], names: &[String], fixed_cost: isize) -> isize {
+        let mut best = 0;
+
+        for mask in 0..(1usize << items.len()) {
+            let subset = (0..items.len()).filter(|i| mask & (1 << i) != 0);
+
+            for ranked in subset.clone().permutations(subset.count()) {
+                if ranked.len() <= names.len() {
+                    best = best.max(total(items, names, fixed_cost, &ranked));
+                }
+            }
+        }
+
+        best
+    }
+
+    #[test]
+    fn shortest_names_for_most_used() {
+        // used 300 times, used 3 times
+        let items = [(300 * 10, 301), (3 * 20, 4)];
+        let names = names(&[1, 2]);
+
+        let v = solve(items.len(), &names, 4, linear(&items));
+
+        assert_eq!(v.ranked, vec![0, 1]);
+        assert_eq!(v.saved, total(&items, &names, 4, &v.ranked));
+    }
+
+    #[test]
+    fn drop_candidate_that_pushes_others_to_longer_names() {
+        // the second candidate saves 1 byte, but pushes the third one to a longer name
+        let items = [(100, 10), (12, 11), (60, 10)];
+        let names = names(&[1, 1, 2]);
+
+        let v = solve(items.len(), &names, 4, linear(&items));
+
+        assert_eq!(v.ranked, vec![0, 2]);
+    }
+
+    #[test]
+    fn nothing_when_fixed_cost_is_not_covered() {
+        let items = [(5, 1), (4, 1)];
+
+        let v = solve(items.len(), &names(&[1, 1]), 10, linear(&items));
+
+        assert_eq!(v, Selection::empty());
+    }
+
+    #[test]
+    fn threshold_applies_to_the_name_taken() {
+        // the second candidate saves 3 bytes with a 1 byte name, 1 byte with a 2 bytes one
+        let items = [(100, 10), (5, 2), (60, 10)];
+        let names = names(&[1, 2, 2]);
+
+        let v = solve_with_threshold(items.len(), &names, 4, 3, linear(&items));
+
+        assert_eq!(v.ranked, vec![0, 2]);
+        assert_eq!(v.saved, total(&items, &names, 4, &v.ranked));
+
+        // below the threshold even with the shortest name
+        let v = solve_with_threshold(items.len(), &names, 4, 50, linear(&items));
+
+        assert_eq!(v.ranked, vec![0]);
+    }
+
+    #[test]
+    fn never_beaten_by_any_subset() {
+        let mut seed = 7u64;
+        let mut rand = |max: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) % max) as isize
+        };
+
+        // each candidate loses a fixed number of bytes per name character
+        for _ in 0..300 {
+            let items = (0..rand(7) + 1)
+                .map(|_| {
+                    let weight = rand(12) + 2;
+                    (weight * (rand(4) + 1) + rand(10) - 8, weight)
+                })
+                .collect::<Vec<_>>();
+            // shorter names first, sometimes fewer than the candidates
+            let mut lens = (0..rand(8) + 1)
+                .map(|_| rand(3) as usize + 1)
+                .collect::<Vec<_>>();
+
+            lens.sort();
+
+            let names = names(&lens);
+            let fixed_cost = rand(6);
+
+            let v = solve(items.len(), &names, fixed_cost, linear(&items));
+
+            assert_eq!(v.saved, total(&items, &names, fixed_cost, &v.ranked));
+            assert_eq!(
+                v.saved,
+                brute_force(&items, &names, fixed_cost),
+                "{:?}",
+                items
+            );
+        }
+    }
+
+    #[test]
+    fn names_depending_on_case() {
+        // a member, and an element only renamed by an uppercase name, `<div>` => `<B>`
+        let saved = |i: usize, name: &str| match i {
+            0 => 50 - 5 * name.len() as isize,
+            _ if name.starts_with(|ch: char| ch.is_ascii_lowercase()) => -6,
+            _ => 30 - 4 * name.len() as isize,
+        };
+
+        let v = solve(2, &["a".to_string(), "B".to_string()], 4, saved);
+
+        assert_eq!(v.ranked, vec![0, 1]);
+        assert_eq!(v.saved, 45 + 26 - 4);
+    }
+
+    #[test]
+    fn greedy_past_the_limit() {
+        let count = MAX_DP_CANDIDATES + 1;
+        // the odd candidates save nothing
+        let items = (0..count)
+            .map(|i| if i % 2 == 0 { (100, 10) } else { (5, 10) })
+            .collect::<Vec<_>>();
+        let names = names(&vec![1; count]);
+
+        let v = solve(count, &names, 4, linear(&items));
+
+        assert_eq!(v.ranked.len(), count.div_ceil(2));
+        assert!(v.ranked.iter().all(|i| i % 2 == 0));
+        assert_eq!(v.saved, total(&items, &names, 4, &v.ranked));
+    }
+}
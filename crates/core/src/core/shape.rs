@@ -0,0 +1,145 @@
+use super::{
+    printed_len::{ident_len, is_ident_name, quoted_len},
+    selection::select_counted,
+    CostStrategy, TokenAllocator,
+};
+
+///
+/// The keys of an object literal in order, created by a hoisted factory instead.
+///
+/// ```unknown
+/// {id:1,name:"x",type:2} => f(1,"x",2), f=(a,b,c)=>({id:a,name:b,type:c})
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectShape {
+    pub keys: Vec<String>,
+}
+
+impl ObjectShape {
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// the parameters of the factory, one for each key
+    pub fn params(&self) -> Vec<String> {
+        let mut allocator = TokenAllocator::new();
+
+        // nothing is reserved but the reserved words, the names never run out
+        self.keys
+            .iter()
+            .map(|_| allocator.alloc().unwrap_or_else(|| unreachable!()))
+            .collect()
+    }
+
+    /// `id` or `"data-id"`
+    fn key_len(key: &str, ascii_only: bool) -> usize {
+        if is_ident_name(key) {
+            ident_len(key, ascii_only)
+        } else {
+            quoted_len(key, ascii_only)
+        }
+    }
+
+    // f=(a,b,c)=>({id:a,name:b,type:c}),
+    fn declaration_cost(&self, name: &str, ascii_only: bool) -> usize {
+        let params = self.params();
+        let commas = self.keys.len() - 1;
+        let props = self
+            .keys
+            .iter()
+            .zip(&params)
+            .map(|(key, param)| Self::key_len(key, ascii_only) + 1 + param.len())
+            .sum::<usize>();
+
+        name.len()
+            + "=(".len()
+            + params.iter().map(|param| param.len()).sum::<usize>()
+            + commas
+            + ")=>({".len()
+            + props
+            + commas
+            + "}),".len()
+    }
+
+    pub fn saved_bytes(&self, name: &str, count: usize, ascii_only: bool) -> isize {
+        if self.keys.is_empty() {
+            return 0;
+        }
+
+        // {id:1,name:"x"} => f(1,"x"), the values and commas stay
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| Self::key_len(key, ascii_only) + 1)
+            .sum::<usize>();
+        let saved = (keys as isize - name.len() as isize) * count as isize;
+
+        saved - self.declaration_cost(name, ascii_only) as isize
+    }
+}
+
+///
+/// Returns the shapes worth a factory and their names, taken from `allocator` in order.
+///
+/// `candidates` are the shapes and how many object literals have each of them.
+///
+pub fn select_shapes(
+    candidates: Vec<(ObjectShape, usize)>,
+    allocator: &TokenAllocator,
+    ascii_only: bool,
+    strategy: &CostStrategy,
+) -> Vec<(ObjectShape, String)> {
+    select_counted(candidates, allocator, strategy, |shape, name, count| {
+        shape.saved_bytes(name, count, ascii_only)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(keys: &[&str]) -> ObjectShape {
+        ObjectShape::new(keys.iter().map(|key| key.to_string()).collect())
+    }
+
+    #[test]
+    fn saved_bytes() {
+        let shape = shape(&["id", "name", "type"]);
+
+        assert_eq!(shape.params(), vec!["a", "b", "c"]);
+        // f=(a,b,c)=>({id:a,name:b,type:c}),
+        assert_eq!(shape.declaration_cost("f", false), 34);
+        // {id:1,name:"x",type:2} => f(1,"x",2)
+        assert_eq!(shape.saved_bytes("f", 3, false), 3 * (13 - 1) - 34);
+    }
+
+    #[test]
+    fn quoted_keys() {
+        // "data-id": => a(
+        assert_eq!(
+            shape(&["data-id"]).saved_bytes("a", 1, false),
+            10 - 1 - "a=(a)=>({\"data-id\":a}),".len() as isize
+        );
+    }
+
+    #[test]
+    fn select() {
+        let selected = select_shapes(
+            vec![
+                (shape(&["id", "name", "type"]), 10),
+                // {x:1,y:2} => b(1,2) saves less than the factory
+                (shape(&["x", "y"]), 2),
+                (shape(&["id", "label"]), 1),
+            ],
+            &TokenAllocator::new(),
+            false,
+            &CostStrategy::default(),
+        );
+
+        assert_eq!(
+            selected,
+            vec![(shape(&["id", "name", "type"]), "a".to_string())]
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// Built-in thresholds, see [`CostStrategy`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CostPreset {
+    /// only hoist the strings that save a noticeable amount of bytes
+    Conservative,
+    /// hoist every string that saves bytes and is longer than 2 bytes
+    #[default]
+    Balanced,
+    /// hoist every string that saves bytes, short ones included
+    Aggressive,
+}
+
+///
+/// The thresholds a string must pass before the cost model considers hoisting it.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Thresholds {
+    /// utf-8 bytes of the string
+    pub min_length: usize,
+    /// usages of the string in the module
+    pub min_occurrences: usize,
+    /// bytes saved by hoisting the string, its declaration included
+    pub min_saved_bytes: usize,
+}
+
+impl CostPreset {
+    pub const fn thresholds(&self) -> Thresholds {
+        match self {
+            CostPreset::Conservative => Thresholds {
+                min_length: 4,
+                min_occurrences: 3,
+                min_saved_bytes: 10,
+            },
+            CostPreset::Balanced => Thresholds {
+                min_length: 3,
+                min_occurrences: 2,
+                min_saved_bytes: 1,
+            },
+            CostPreset::Aggressive => Thresholds {
+                min_length: 1,
+                min_occurrences: 2,
+                min_saved_bytes: 1,
+            },
+        }
+    }
+}
+
+///
+/// Selects the thresholds of the cost model, a preset and the values overriding it.
+///
+/// ```json
+/// { "preset": "conservative", "minOccurrences": 5 }
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CostStrategy {
+    pub preset: CostPreset,
+    pub min_length: Option<usize>,
+    pub min_occurrences: Option<usize>,
+    pub min_saved_bytes: Option<usize>,
+}
+
+impl CostStrategy {
+    pub fn thresholds(&self) -> Thresholds {
+        let preset = self.preset.thresholds();
+
+        Thresholds {
+            min_length: self.min_length.unwrap_or(preset.min_length),
+            min_occurrences: self.min_occurrences.unwrap_or(preset.min_occurrences),
+            // hoisting that saves nothing is never worth it
+            min_saved_bytes: self
+                .min_saved_bytes
+                .unwrap_or(preset.min_saved_bytes)
+                .max(1),
+        }
+    }
+}
+
+impl From<CostPreset> for CostStrategy {
+    fn from(preset: CostPreset) -> Self {
+        Self {
+            preset,
+            ..Default::default()
+        }
+    }
+}
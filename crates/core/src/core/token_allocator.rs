@@ -1,79 +1,41 @@
-use rustc_hash::FxHashSet;
-use lazy_static::lazy_static;
-
-use super::constant;
-
-lazy_static! {
-    static ref PRESERVE_KEYWORDS: FxHashSet<&'static str> = {
-        FxHashSet::from_iter([
-            "break",
-            "case",
-            "catch",
-            "class",
-            "const",
-            "continue",
-            "debugger",
-            "default",
-            "delete",
-            "do",
-            "else",
-            "enum",
-            "export",
-            "extends",
-            "false",
-            "finally",
-            "for",
-            "function",
-            "if",
-            "import",
-            "in",
-            "instanceof",
-            "new",
-            "null",
-            "return",
-            "super",
-            "switch",
-            "this",
-            "throw",
-            "true",
-            "try",
-            "typeof",
-            "var",
-            "void",
-            "while",
-            "with",
-            "as",
-            "implements",
-            "interface",
-            "let",
-            "package",
-            "private",
-            "protected",
-            "public",
-            "static",
-            "yield",
-            "any",
-            "boolean",
-            "constructor",
-            "declare",
-            "get",
-            "module",
-            "require",
-            "number",
-            "set",
-            "string",
-            "symbol",
-            "type",
-            "from",
-            "of",
-        ])
-    };
-}
+use std::cmp::Reverse;
+
+use itertools::Itertools;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::{constant, is_ident_name, ReservedNames};
 
-#[derive(Debug, Default)]
+/// the names skipped in a row before [`TokenAllocator::alloc`] gives up
+const MAX_SKIPPED: usize = 1 << 20;
+
+#[derive(Debug, Clone)]
 pub struct TokenAllocator {
     pos: usize,
+    /// [`constant::LEADING_CHARACTERS`], in the order they are handed out
+    leading: Vec<u8>,
+    /// [`constant::TRAILING_CHARACTERS`], in the order they are handed out
+    trailing: Vec<u8>,
     used_ident: FxHashSet<String>,
+    /// the reserved words are always included
+    reserved: ReservedNames,
+    /// string => name, kept from the previous builds
+    cache: FxHashMap<String, String>,
+    /// no name is left, see [`TokenAllocator::alloc`]
+    exhausted: bool,
+}
+
+impl Default for TokenAllocator {
+    fn default() -> Self {
+        Self {
+            pos: 0,
+            leading: constant::LEADING_CHARACTERS.to_vec(),
+            trailing: constant::TRAILING_CHARACTERS.to_vec(),
+            used_ident: Default::default(),
+            reserved: Default::default(),
+            cache: Default::default(),
+            exhausted: false,
+        }
+    }
 }
 
 impl TokenAllocator {
@@ -81,64 +43,176 @@ impl TokenAllocator {
         Default::default()
     }
 
+    ///
+    /// Hands out the characters that appear the most in `code` first, like the mangler of terser.
+    ///
+    /// The names then reuse the characters already common in the chunk, which compresses
+    /// better. The characters appearing equally often keep their default order.
+    ///
+    pub fn with_char_frequency(mut self, code: &[u8]) -> Self {
+        let mut counts = [0usize; 256];
+
+        code.iter().for_each(|ch| counts[*ch as usize] += 1);
+
+        let order = |chars: &[u8]| {
+            chars
+                .iter()
+                .copied()
+                .sorted_by_key(|ch| Reverse(counts[*ch as usize]))
+                .collect()
+        };
+
+        self.leading = order(constant::LEADING_CHARACTERS);
+        self.trailing = order(constant::TRAILING_CHARACTERS);
+
+        self
+    }
+
     pub fn extends(&mut self, set: FxHashSet<String>) {
         self.used_ident.extend(set);
     }
 
+    pub fn with_reserved(mut self, reserved: ReservedNames) -> Self {
+        self.reserved = reserved;
+        self
+    }
+
+    ///
+    /// Keeps the names of `cache` (string => name) for the same strings, see
+    /// [`TokenAllocator::alloc_for`].
+    ///
+    /// Every usable name of `cache` is reserved, even when its string is not in this module,
+    /// so it stays free for that string in the next build. The names that can not be used
+    /// here, e.g. a local variable already has it, are dropped. Call it after
+    /// [`TokenAllocator::extends`].
+    ///
+    pub fn with_cache<I: IntoIterator<Item = (String, String)>>(mut self, cache: I) -> Self {
+        for (value, name) in cache {
+            if !is_ident_name(&name) || !self.allocable(&name) {
+                continue;
+            }
+
+            self.used_ident.insert(name.clone());
+            self.cache.insert(value, name);
+        }
+
+        self
+    }
+
     pub fn allocable(&self, ident: &str) -> bool {
-        !(PRESERVE_KEYWORDS.contains(ident) || self.used_ident.contains(ident))
+        !(self.reserved.contains(ident) || self.used_ident.contains(ident))
     }
 
+    ///
+    /// The `pos`-th name, shorter names first.
+    ///
+    /// The first character is one of [`constant::LEADING_CHARACTERS`], the others are
+    /// [`constant::TRAILING_CHARACTERS`], so there are 54 names of one character,
+    /// 54 * 64 of two characters, and so on. The characters are taken in the order
+    /// of [`TokenAllocator::with_char_frequency`] when it is set.
+    ///
     fn ident(&self) -> String {
-        let mut pos = self.pos / constant::COMPRESS_CHARACTER_WIDTH as usize;
-        let mut r = String::new();
-
-        let mut push_ch = |ch: u8| {
-            r.push(if ch < 26 {
-                (b'a' + ch) as char
-            } else {
-                (b'A' + (ch - 26)) as char
-            });
-        };
+        let leading = self.leading.len();
+        let trailing = self.trailing.len();
+
+        // names of the current length, and the position among them
+        let mut count = leading;
+        let mut pos = self.pos;
+        let mut len = 1;
 
-        while pos > 0 {
-            let ch = (pos - 1) % constant::COMPRESS_CHARACTER_WIDTH as usize;
+        while pos >= count {
+            pos -= count;
+            count *= trailing;
+            len += 1;
+        }
 
-            push_ch(ch as u8);
+        let mut r = vec![0u8; len];
 
-            pos /= constant::COMPRESS_CHARACTER_WIDTH as usize;
+        for ch in r.iter_mut().skip(1).rev() {
+            *ch = self.trailing[pos % trailing];
+            pos /= trailing;
         }
 
-        push_ch((self.pos % constant::COMPRESS_CHARACTER_WIDTH as usize) as u8);
+        r[0] = self.leading[pos];
+
+        String::from_utf8(r).unwrap()
+    }
 
-        r
+    /// the name `value` keeps from the cache
+    pub fn cached(&self, value: &str) -> Option<&str> {
+        self.cache.get(value).map(String::as_str)
     }
 
-    pub fn alloc(&mut self) -> String {
-        loop {
+    /// the cached name of `value`, or a new one
+    pub fn alloc_for(&mut self, value: &str) -> Option<String> {
+        match self.cache.get(value) {
+            Some(name) => Some(name.clone()),
+            None => self.alloc(),
+        }
+    }
+
+    ///
+    /// The next free name, `None` when there is none left.
+    ///
+    /// The reserved patterns may take every name, e.g. `.*`, so the search stops after
+    /// [`MAX_SKIPPED`] names are skipped in a row. It never hands out a name again after that.
+    ///
+    pub fn alloc(&mut self) -> Option<String> {
+        let mut skipped = 0;
+
+        while !self.exhausted {
             let s = self.ident();
             self.pos += 1;
 
             if self.allocable(&s) {
                 self.used_ident.insert(s.clone());
-                return s;
+                return Some(s);
             }
+
+            skipped += 1;
+            self.exhausted = skipped >= MAX_SKIPPED;
         }
+
+        None
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::{EnvProfile, ReservedOption};
+
+    fn ident_at(pos: usize) -> String {
+        TokenAllocator {
+            pos,
+            ..Default::default()
+        }
+        .ident()
+    }
+
+    #[test]
+    fn ident() {
+        assert_eq!(ident_at(0), "a");
+        assert_eq!(ident_at(51), "Z");
+        assert_eq!(ident_at(52), "$");
+        assert_eq!(ident_at(53), "_");
+        assert_eq!(ident_at(54), "aa");
+        assert_eq!(ident_at(117), "a9");
+        assert_eq!(ident_at(118), "ba");
+        assert_eq!(ident_at(54 + 54 * 64 - 1), "_9");
+        assert_eq!(ident_at(54 + 54 * 64), "aaa");
+    }
 
     #[test]
     fn ident_alloc() {
         let mut token = TokenAllocator::new();
 
-        let v = (0..200).map(|_| token.alloc()).collect::<Vec<_>>();
+        let v = (0..300).map_while(|_| token.alloc()).collect::<Vec<_>>();
 
         assert_eq!(v[0], "a");
-        assert_eq!(v[199], "cS");
+        assert_eq!(v[199], "cr");
+        // `do` is skipped
+        assert_eq!(v[54 + 64 * 3 + 14], "dp");
     }
 
     #[test]
@@ -147,9 +221,93 @@ mod tests {
 
         token.used_ident.insert("b".to_string());
 
-        let v = (0..200).map(|_| token.alloc()).collect::<Vec<_>>();
+        let v = (0..200).map_while(|_| token.alloc()).collect::<Vec<_>>();
 
         assert_eq!(v[0], "a");
-        assert_eq!(v[199], "cT");
+        assert_eq!(v[199], "cs");
+    }
+
+    #[test]
+    fn alloc_with_char_frequency() {
+        let mut token = TokenAllocator::new().with_char_frequency(b"x.e1 = xe1e1 + 'e';");
+
+        let v = (0..56).map_while(|_| token.alloc()).collect::<Vec<_>>();
+
+        assert_eq!(&v[..4], ["e", "x", "a", "b"]);
+        assert_eq!(v[53], "_");
+        assert_eq!(&v[54..], ["ee", "e1"]);
+    }
+
+    #[test]
+    fn alloc_with_reserved() -> Result<(), regex::Error> {
+        let reserved = ReservedOption {
+            profiles: vec![EnvProfile::Browser],
+            patterns: vec!["^[a-c]$".to_string()],
+            ..Default::default()
+        }
+        .compile()?;
+
+        let mut token = TokenAllocator::new().with_reserved(reserved);
+
+        let v = (0..53).map_while(|_| token.alloc()).collect::<Vec<_>>();
+
+        assert_eq!(v[0], "d");
+        // `$` and `_` are skipped
+        assert_eq!(v[48], "Z");
+        assert_eq!(v[49], "aa");
+
+        Ok(())
+    }
+
+    #[test]
+    fn alloc_with_cache() {
+        let mut token = TokenAllocator::new();
+
+        token.extends(FxHashSet::from_iter(["c".to_string()]));
+
+        let mut token = token.with_cache([
+            ("foo".to_string(), "b".to_string()),
+            ("bar".to_string(), "a".to_string()),
+            // can not be used in this module
+            ("baz".to_string(), "c".to_string()),
+            ("qux".to_string(), "if".to_string()),
+        ]);
+
+        assert_eq!(token.alloc_for("foo").as_deref(), Some("b"));
+        assert_eq!(token.alloc_for("new").as_deref(), Some("d"));
+        assert_eq!(token.alloc_for("baz").as_deref(), Some("e"));
+        assert_eq!(token.alloc_for("qux").as_deref(), Some("f"));
+        // `a` is kept for `bar`
+        assert_eq!(token.alloc().as_deref(), Some("g"));
+    }
+
+    #[test]
+    fn alloc_when_every_name_is_reserved() -> Result<(), regex::Error> {
+        for pattern in [".*", "^[a-zA-Z$_]"] {
+            let reserved = ReservedOption {
+                patterns: vec![pattern.to_string()],
+                ..Default::default()
+            }
+            .compile()?;
+
+            let mut token = TokenAllocator::new().with_reserved(reserved);
+
+            assert_eq!(token.alloc(), None, "{}", pattern);
+            assert_eq!(token.alloc_for("foo"), None, "{}", pattern);
+        }
+
+        // only `a` is left
+        let reserved = ReservedOption {
+            patterns: vec!["^[^a]|^a.".to_string()],
+            ..Default::default()
+        }
+        .compile()?;
+
+        let mut token = TokenAllocator::new().with_reserved(reserved);
+
+        assert_eq!(token.alloc().as_deref(), Some("a"));
+        assert_eq!(token.alloc(), None);
+
+        Ok(())
     }
 }
@@ -0,0 +1,478 @@
+use itertools::Itertools;
+use omm_core::{select_aliases, ChainAlias, TokenAllocator};
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_common::{Mark, Span, DUMMY_SP};
+use swc_ecma_ast::{
+    ArrowExpr, AssignExpr, AssignOp, AssignTarget, BinExpr, BinaryOp, BindingIdent, CallExpr,
+    Callee, Class, CondExpr, DoWhileStmt, Expr, ExprOrSpread, ForInStmt, ForOfStmt, ForStmt,
+    Function, GetterProp, Ident, IdentName, IfStmt, MemberExpr, MemberProp, Module, OptChainBase,
+    OptChainExpr, Pat, SetterProp, SimpleAssignTarget, SwitchStmt, TaggedTpl, TryStmt, UnaryExpr,
+    UnaryOp, UpdateExpr, VarDeclarator, WhileStmt,
+};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use crate::{transformer::TransformContext, util::declare_hoisted};
+
+/// The globals of the language, defined wherever the module runs.
+const BUILTIN_OBJECTS: [&str; 22] = [
+    "Array",
+    "ArrayBuffer",
+    "BigInt",
+    "Boolean",
+    "DataView",
+    "Date",
+    "Error",
+    "Function",
+    "JSON",
+    "Map",
+    "Math",
+    "Number",
+    "Object",
+    "Promise",
+    "Reflect",
+    "RegExp",
+    "Set",
+    "String",
+    "Symbol",
+    "WeakMap",
+    "WeakSet",
+    "globalThis",
+];
+
+///
+/// Collects the usages of the member chains listed in
+/// [`crate::TransformOption::global_aliases`], whose root is a global that is not shadowed.
+///
+/// A chain assigned anywhere in the module, or one of its objects, is never aliased.
+///
+struct ChainCollector<'a> {
+    unresolved_mark: Mark,
+    allowlist: &'a FxHashSet<String>,
+    sites: FxHashMap<ChainAlias, Vec<Span>>,
+    /// the chains assigned, updated or deleted
+    written: FxHashSet<String>,
+    /// every name of the module, the aliases can not take them
+    idents: FxHashSet<String>,
+    /// the chains read whenever the module is evaluated
+    eager: FxHashSet<ChainAlias>,
+    /// inside a function, a branch or `typeof`, which may not run when the module is evaluated
+    deferred: usize,
+}
+
+impl<'a> ChainCollector<'a> {
+    fn new(unresolved_mark: Mark, allowlist: &'a FxHashSet<String>) -> Self {
+        Self {
+            unresolved_mark,
+            allowlist,
+            sites: Default::default(),
+            written: Default::default(),
+            idents: Default::default(),
+            eager: Default::default(),
+            deferred: 0,
+        }
+    }
+
+    fn deferred<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.deferred += 1;
+        f(self);
+        self.deferred -= 1;
+    }
+
+    /// `Object.defineProperty`, only given a global root and identifier properties
+    fn chain(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Ident(ident) if ident.ctxt.outer() == self.unresolved_mark => {
+                Some(ident.sym.to_string())
+            }
+            Expr::Member(member) => self.member_chain(member),
+            _ => None,
+        }
+    }
+
+    fn member_chain(&self, member: &MemberExpr) -> Option<String> {
+        match &member.prop {
+            MemberProp::Ident(prop) => Some(format!("{}.{}", self.chain(&member.obj)?, prop.sym)),
+            _ => None,
+        }
+    }
+
+    fn count(&mut self, member: &MemberExpr, bound: bool) -> bool {
+        let Some(chain) = self.member_chain(member) else {
+            return false;
+        };
+
+        if !self.allowlist.contains(&chain) {
+            return false;
+        }
+
+        let alias = ChainAlias::new(&chain, bound);
+
+        if self.deferred == 0 {
+            self.eager.insert(alias.clone());
+        }
+
+        self.sites.entry(alias).or_default().push(member.span);
+
+        // the root is not visited, e.g. `$` of `$.ajax`
+        if let Some((root, _)) = chain.split_once('.') {
+            self.idents.insert(root.to_string());
+        }
+
+        true
+    }
+
+    /// `a.b` of `a.b?.()` or `` a.b`` ``, it is not a site, the objects are
+    fn visit_callee(&mut self, callee: &Expr) {
+        match callee {
+            Expr::Member(member) => {
+                member.obj.visit_with(self);
+                member.prop.visit_with(self);
+            }
+            _ => callee.visit_with(self),
+        }
+    }
+
+    fn write(&mut self, target: &Expr) {
+        if let Some(chain) = self.chain(target) {
+            self.written.insert(chain);
+        }
+    }
+
+    fn write_member(&mut self, target: &MemberExpr) {
+        if let Some(chain) = self.member_chain(target) {
+            self.written.insert(chain);
+        }
+
+        target.obj.visit_with(self);
+        target.prop.visit_with(self);
+    }
+
+    ///
+    /// Whether reading the chain when the module is evaluated, instead of at each usage, does
+    /// not throw where the module did not, e.g. `document` in a worker.
+    ///
+    fn is_eager(&self, alias: &ChainAlias) -> bool {
+        let root = alias.chain.split('.').next().unwrap_or_default();
+
+        BUILTIN_OBJECTS.contains(&root) || self.eager.contains(alias)
+    }
+
+    fn is_written(&self, chain: &str) -> bool {
+        self.written.iter().any(|written| {
+            chain == written
+                || chain
+                    .strip_prefix(written.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+}
+
+impl Visit for ChainCollector<'_> {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.idents.insert(ident.sym.to_string());
+    }
+
+    fn visit_member_expr(&mut self, node: &MemberExpr) {
+        // the longest chain wins, `Array.prototype.slice` over `Array.prototype`
+        if !self.count(node, false) {
+            node.visit_children_with(self);
+        }
+    }
+
+    fn visit_call_expr(&mut self, node: &CallExpr) {
+        match &node.callee {
+            Callee::Expr(box Expr::Member(member)) if self.count(member, true) => {}
+            Callee::Expr(callee) => self.visit_callee(callee),
+            callee => callee.visit_with(self),
+        }
+
+        node.args.visit_with(self);
+    }
+
+    fn visit_function(&mut self, node: &Function) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_getter_prop(&mut self, node: &GetterProp) {
+        node.key.visit_with(self);
+        self.deferred(|this| node.body.visit_with(this));
+    }
+
+    fn visit_setter_prop(&mut self, node: &SetterProp) {
+        node.key.visit_with(self);
+        self.deferred(|this| {
+            node.param.visit_with(this);
+            node.body.visit_with(this);
+        });
+    }
+
+    // the static blocks and fields run with the class, but are rarely worth telling apart
+    fn visit_class(&mut self, node: &Class) {
+        node.super_class.visit_with(self);
+        self.deferred(|this| node.body.visit_with(this));
+    }
+
+    fn visit_if_stmt(&mut self, node: &IfStmt) {
+        node.test.visit_with(self);
+        self.deferred(|this| {
+            node.cons.visit_with(this);
+            node.alt.visit_with(this);
+        });
+    }
+
+    fn visit_cond_expr(&mut self, node: &CondExpr) {
+        node.test.visit_with(self);
+        self.deferred(|this| {
+            node.cons.visit_with(this);
+            node.alt.visit_with(this);
+        });
+    }
+
+    fn visit_bin_expr(&mut self, node: &BinExpr) {
+        node.left.visit_with(self);
+
+        match node.op {
+            BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing => {
+                self.deferred(|this| node.right.visit_with(this));
+            }
+            _ => node.right.visit_with(self),
+        }
+    }
+
+    fn visit_switch_stmt(&mut self, node: &SwitchStmt) {
+        node.discriminant.visit_with(self);
+        self.deferred(|this| node.cases.visit_with(this));
+    }
+
+    // a throw is caught, and a loop may not run
+    fn visit_try_stmt(&mut self, node: &TryStmt) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_while_stmt(&mut self, node: &WhileStmt) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_do_while_stmt(&mut self, node: &DoWhileStmt) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_for_stmt(&mut self, node: &ForStmt) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_for_in_stmt(&mut self, node: &ForInStmt) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_for_of_stmt(&mut self, node: &ForOfStmt) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_opt_chain_expr(&mut self, node: &OptChainExpr) {
+        self.deferred(|this| node.visit_children_with(this));
+    }
+
+    fn visit_opt_chain_base(&mut self, node: &OptChainBase) {
+        match node {
+            // `a.b?.c`, `a?.b` is not a chain
+            OptChainBase::Member(member) => {
+                member.obj.visit_with(self);
+                member.prop.visit_with(self);
+            }
+            OptChainBase::Call(call) => {
+                self.visit_callee(&call.callee);
+                call.args.visit_with(self);
+            }
+        }
+    }
+
+    fn visit_tagged_tpl(&mut self, node: &TaggedTpl) {
+        self.visit_callee(&node.tag);
+        node.tpl.visit_with(self);
+    }
+
+    fn visit_assign_expr(&mut self, node: &AssignExpr) {
+        match &node.left {
+            AssignTarget::Simple(SimpleAssignTarget::Member(member)) => {
+                self.write_member(member);
+            }
+            AssignTarget::Simple(SimpleAssignTarget::Ident(BindingIdent { id, .. })) => {
+                self.write(&Expr::Ident(id.clone()));
+                id.visit_with(self);
+            }
+            left => left.visit_with(self),
+        }
+
+        match node.op {
+            AssignOp::AndAssign | AssignOp::OrAssign | AssignOp::NullishAssign => {
+                self.deferred(|this| node.right.visit_with(this));
+            }
+            _ => node.right.visit_with(self),
+        }
+    }
+
+    fn visit_pat(&mut self, node: &Pat) {
+        // `[Object.foo] = a`
+        match node {
+            Pat::Expr(expr) => {
+                self.write(expr);
+                self.visit_callee(expr);
+            }
+            _ => node.visit_children_with(self),
+        }
+    }
+
+    fn visit_update_expr(&mut self, node: &UpdateExpr) {
+        self.write(&node.arg);
+        self.visit_callee(&node.arg);
+    }
+
+    fn visit_unary_expr(&mut self, node: &UnaryExpr) {
+        match node.op {
+            UnaryOp::Delete => {
+                self.write(&node.arg);
+                self.visit_callee(&node.arg);
+            }
+            // `typeof document` guards what it reads
+            UnaryOp::TypeOf => self.deferred(|this| node.arg.visit_with(this)),
+            _ => node.arg.visit_with(self),
+        }
+    }
+}
+
+/// Replaces the sites of the selected aliases, keyed by their span.
+pub struct ChainReplacer {
+    pub sites: FxHashMap<Span, String>,
+}
+
+impl VisitMut for ChainReplacer {
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        if let Expr::Member(member) = node
+            && let Some(name) = self.sites.get(&member.span)
+        {
+            *node = Expr::Ident(name.as_str().into());
+            return;
+        }
+
+        node.visit_mut_children_with(self);
+    }
+}
+
+/// `Object.defineProperty` as an expression
+fn chain_expr(chain: &str) -> Expr {
+    let mut parts = chain.split('.');
+    let root = Expr::Ident(parts.next().unwrap_or_default().into());
+
+    parts.fold(root, |obj, prop| {
+        Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(obj),
+            prop: MemberProp::Ident(IdentName::new(prop.into(), DUMMY_SP)),
+        })
+    })
+}
+
+// document.createElement.bind(document)
+fn init_expr(alias: &ChainAlias) -> Expr {
+    let chain = chain_expr(&alias.chain);
+
+    if !alias.bound {
+        return chain;
+    }
+
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        ctxt: Default::default(),
+        callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(chain),
+            prop: MemberProp::Ident(IdentName::new("bind".into(), DUMMY_SP)),
+        }))),
+        args: vec![ExprOrSpread {
+            spread: None,
+            expr: Box::new(chain_expr(alias.object())),
+        }],
+        type_args: None,
+    })
+}
+
+///
+/// Aliases the repeated member chains of the globals listed in
+/// [`crate::TransformOption::global_aliases`] with variables declared at the top of the module.
+///
+/// ```unknown
+/// Object.defineProperty(a, "foo", {}) => a(a, "foo", {})
+/// document.createElement("div")       => b("div")
+///
+/// var a = Object.defineProperty, b = document.createElement.bind(document);
+/// ```
+///
+/// The chains are read once, when the module is evaluated. So a chain is only aliased when
+/// its root is a global of the language, e.g. `Object`, or when the module already reads it
+/// when evaluated, outside any function, branch or `typeof`. `document.body` only read in a
+/// function may not exist when the module is evaluated, e.g. in a worker.
+///
+pub fn alias_global_chains(module: &mut Module, context: &TransformContext, unresolved_mark: Mark) {
+    let allowlist = context
+        .options
+        .global_aliases
+        .iter()
+        .filter(|chain| chain.contains('.'))
+        .cloned()
+        .collect::<FxHashSet<_>>();
+
+    let mut collector = ChainCollector::new(unresolved_mark, &allowlist);
+
+    module.visit_with(&mut collector);
+
+    let candidates = collector
+        .sites
+        .iter()
+        .filter(|(alias, _)| collector.is_eager(alias) && !collector.is_written(&alias.chain))
+        .map(|(alias, sites)| (alias.clone(), sites.len()))
+        .collect::<Vec<_>>();
+
+    let mut allocator = TokenAllocator::new().with_reserved(context.reserved.clone());
+
+    allocator.extends(collector.idents.clone());
+
+    let selected = select_aliases(candidates, &allocator, &context.options.cost_strategy);
+
+    if selected.is_empty() {
+        return;
+    }
+
+    let mut replacer = ChainReplacer {
+        sites: selected
+            .iter()
+            .flat_map(|(alias, name)| {
+                collector.sites[alias]
+                    .iter()
+                    .map(move |span| (*span, name.clone()))
+            })
+            .collect(),
+    };
+
+    module.visit_mut_with(&mut replacer);
+
+    let decls = selected
+        .iter()
+        .sorted_by_key(|(_, name)| name.to_string())
+        .map(|(alias, name)| VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: name.as_str().into(),
+                type_ann: None,
+            }),
+            init: Some(Box::new(init_expr(alias))),
+            definite: false,
+        })
+        .collect();
+
+    declare_hoisted(module, decls);
+}
@@ -1,19 +1,35 @@
 use std::{cell::RefCell, fmt::Debug, rc::Rc};
 
+use omm_core::{OccurrenceKind, Occurrences};
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use swc_common::{util::take::Take, Mark, Span, Spanned};
 use swc_ecma_ast::{
-    CallExpr, Callee, Expr, Ident, IdentName, Lit, MemberExpr, MemberProp, Prop, PropName, Str,
+    ArrowExpr, BlockStmtOrExpr, CallExpr, Callee, Constructor, ExportAll, Expr, Function, Ident,
+    IdentName, ImportDecl, JSXAttrValue, JSXElementName, Lit, MemberExpr, MemberProp, Module,
+    NamedExport, ObjectPatProp, Prop, PropName, Str, SuperProp, SuperPropExpr, TaggedTpl, Tpl,
 };
 use swc_ecma_visit::{Visit, VisitWith};
 
-use crate::transformer::{IgnoreWord, StringLitOptions, TransformContext};
+use crate::{
+    transformer::{IgnoreWord, StringLitOptions, TransformContext},
+    util::{constant_key, function_prologue, member_chain, module_prologue, tpl_as_str},
+};
 
-pub type IdentCollectorData = FxHashMap<String, (FxHashSet<Span>, usize)>;
+pub type IdentCollectorData = FxHashMap<String, (FxHashMap<Span, OccurrenceKind>, Occurrences)>;
 type IgnoreWordTrieValue = (usize, IgnoreWord);
 type MatchedResult = Option<(usize, Option<Rc<IgnoreWordTrieValue>>)>;
 
+///
+/// The names seen in a function, they only block the hoisted names when the hoisted
+/// variables may be referenced inside it.
+///
+#[derive(Debug, Default)]
+struct ScopeFrame {
+    names: FxHashSet<String>,
+    has_candidate: bool,
+}
+
 #[derive(Debug, Default, Clone)]
 struct PendingStoreArg {
     arg_range: FxHashSet<Span>,
@@ -29,14 +45,18 @@ pub struct IdentCollector {
     // TODO: collect more detailed data, such as variable declarations, parameters, functions, etc.
     pub unresolved_ident: FxHashSet<String>,
     pub top_level_ident: FxHashSet<String>,
+    /// the names the hoisted variables can not take
     pub used_ident: FxHashSet<String>,
     pub top_level_mark: Mark,
     pub unresolved_mark: Mark,
+    /// the first of the identical constants, keyed by [`constant_key`]
+    pub constants: FxHashMap<String, Lit>,
     trie: Trie<(usize, IgnoreWord)>,
     state: CollectorMemberMatcherState,
     pending_store_arg: PendingStoreArg,
     // contain_member_match_list: ContainMemberMatch,
     skip_strings: FxHashSet<String>,
+    scopes: Vec<ScopeFrame>,
 }
 
 impl IdentCollector {
@@ -48,43 +68,74 @@ impl IdentCollector {
             top_level_mark,
             unresolved_mark,
             used_ident: Default::default(),
+            constants: Default::default(),
             trie: Trie::new(),
             state: CollectorMemberMatcherState::default(),
             skip_lits: Default::default(),
             pending_store_arg: Default::default(),
             skip_strings: FxHashSet::default(),
             skip_ranges: Default::default(),
+            scopes: Default::default(),
         }
     }
 
-    fn count_str(&mut self, ident: &str, span: Span) {
+    fn count_str(&mut self, ident: &str, span: Span, kind: OccurrenceKind) {
         if self.skip_lits.contains(&span) {
             return;
         }
 
-        let (spans, count) = self
+        let (spans, occurrences) = self
             .field
             .entry(ident.to_string())
-            .or_insert_with(|| (FxHashSet::default(), 0));
+            .or_insert_with(|| (FxHashMap::default(), Occurrences::new()));
+
+        spans.insert(span, kind);
+        occurrences.add(kind);
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.has_candidate = true;
+        }
+    }
+
+    /// `"use strict"` is no longer a directive once it is replaced
+    fn skip_prologue<'a>(&mut self, prologue: impl Iterator<Item = &'a Str>) {
+        self.skip_lits.extend(prologue.map(|str| str.span));
+    }
+
+    fn count_lit(&mut self, ident: &Str, kind: OccurrenceKind) {
+        self.count_value(&ident.value, ident.span, kind);
+    }
+
+    /// a string written by the user, skipped when it is ignored
+    fn count_value(&mut self, value: &str, span: Span, kind: OccurrenceKind) {
+        if self.skip_strings.contains(value) {
+            return;
+        }
 
-        spans.insert(span);
-        *count += 1;
+        self.count_str(value, span, kind);
     }
 
-    fn count_lit(&mut self, ident: &Str) {
-        if self.skip_strings.contains(ident.value.as_ref()) {
+    fn count_constant(&mut self, lit: &Lit, key: String) {
+        // `0` or `10` saves a byte at most per usage, not worth blocking the names of the scope
+        if key.len() <= 2 {
             return;
         }
 
-        self.count_str(&ident.value, ident.span);
+        self.count_str(&key, lit.span(), OccurrenceKind::Constant);
+        self.constants.entry(key).or_insert_with(|| {
+            let mut lit = lit.clone();
+
+            lit.set_span(Default::default());
+            lit
+        });
     }
 
-    fn count_ident(&mut self, ident: &Ident) {
-        self.count_str(&ident.sym, ident.span);
+    fn count_ident(&mut self, ident: &Ident, kind: OccurrenceKind) {
+        self.count_str(&ident.sym, ident.span, kind);
     }
 
-    fn count_ident_name(&mut self, ident: &IdentName) {
-        self.count_str(&ident.sym, ident.span);
+    fn count_ident_name(&mut self, ident: &IdentName, kind: OccurrenceKind) {
+        self.count_str(&ident.sym, ident.span, kind);
     }
 
     pub fn with_context(mut self, context: &TransformContext) -> Self {
@@ -109,8 +160,42 @@ impl IdentCollector {
         self.state = prev;
     }
 
+    ///
+    /// Top level and global names are always blocked, the hoisted variables are declared at
+    /// the top level. The names local to a function are only blocked when a string in it
+    /// may be replaced, see [`IdentCollector::with_scope`].
+    ///
     fn collect_used_ident(&mut self, ident: &Ident) {
-        self.used_ident.insert(ident.sym.to_string());
+        let name = ident.sym.to_string();
+        let mark = ident.ctxt.outer();
+
+        if mark == self.top_level_mark {
+            self.top_level_ident.insert(name.clone());
+        } else if mark == self.unresolved_mark {
+            self.unresolved_ident.insert(name.clone());
+        } else if let Some(scope) = self.scopes.last_mut() {
+            scope.names.insert(name);
+            return;
+        }
+
+        self.used_ident.insert(name);
+    }
+
+    fn with_scope<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.scopes.push(ScopeFrame::default());
+
+        f(self);
+
+        let scope = self.scopes.pop().unwrap_or_default();
+
+        // a replaced string is also inside the parent functions
+        if scope.has_candidate {
+            self.used_ident.extend(scope.names);
+
+            if let Some(parent) = self.scopes.last_mut() {
+                parent.has_candidate = true;
+            }
+        }
     }
 
     fn process_arg_lits(&mut self, arg_lits: FxHashSet<(Str, Span)>, match_result: &MatchedResult) {
@@ -135,7 +220,7 @@ impl IdentCollector {
         }
 
         arg_lits.into_iter().for_each(|(lit, _)| {
-            self.count_lit(&lit);
+            self.count_lit(&lit, OccurrenceKind::Lit);
         });
     }
 
@@ -147,8 +232,8 @@ impl IdentCollector {
             ..
         } = matcher_result;
 
-        for (ident, span) in ident_list {
-            self.count_str(&ident, span);
+        for (ident, span, kind) in ident_list {
+            self.count_str(&ident, span, kind);
         }
 
         if matched {
@@ -166,6 +251,8 @@ impl IdentCollector {
             let (is_matched, matched_option) = match expr {
                 Expr::Member(member) => self.process_member_expr(member),
                 Expr::Ident(ident) => {
+                    self.collect_used_ident(ident);
+
                     let mut matcher = MemberMatcher::new(&self.trie);
 
                     ident.visit_with(&mut matcher);
@@ -186,11 +273,18 @@ impl IdentCollector {
                     node.args
                         .iter()
                         .filter_map(|arg| {
-                            if let Expr::Lit(Lit::Str(lit)) = &*arg.expr {
-                                Some((lit.clone(), arg.span()))
-                            } else {
-                                None
-                            }
+                            let lit = match &*arg.expr {
+                                Expr::Lit(Lit::Str(lit)) => lit.clone(),
+                                // `` `./foo` `` is the same as `"./foo"`
+                                Expr::Tpl(tpl) => Str {
+                                    span: tpl.span,
+                                    value: tpl_as_str(tpl)?.into(),
+                                    raw: None,
+                                },
+                                _ => return None,
+                            };
+
+                            Some((lit, arg.span()))
                         })
                         .collect(),
                     &matched_option,
@@ -254,7 +348,16 @@ impl IdentCollector {
                     member.visit_with(self);
                 }
                 box Expr::Ident(ident) => {
-                    self.collect_used_ident(&ident);
+                    self.collect_used_ident(ident);
+                }
+                // `/foo/i.test(a)`, a regex stored, passed or changed elsewhere, e.g. with
+                // `lastIndex`, may be changed by another usage once it is shared
+                box Expr::Lit(lit @ Lit::Regex(_)) => {
+                    if is_regex_read(&node.prop)
+                        && let Some(key) = constant_key(lit)
+                    {
+                        self.count_constant(lit, key);
+                    }
                 }
                 _ => {
                     self.with_state(CollectorMemberMatcherState::Visitor, |this| {
@@ -266,13 +369,13 @@ impl IdentCollector {
             match &node.prop {
                 MemberProp::Ident(ident_name) => {
                     if !is_match_mode {
-                        self.count_ident_name(ident_name);
+                        self.count_ident_name(ident_name, OccurrenceKind::Member);
                     }
                 }
                 MemberProp::PrivateName(_) => {}
                 MemberProp::Computed(computed_prop_name) => {
                     if !is_match_mode && let Expr::Lit(Lit::Str(lit)) = &*computed_prop_name.expr {
-                        self.count_lit(lit);
+                        self.count_lit(lit, OccurrenceKind::Lit);
                         return (is_matched, matched_options);
                     }
                     self.with_state(CollectorMemberMatcherState::Visitor, |this| {
@@ -299,6 +402,50 @@ impl Visit for IdentCollector {
         self.process_member_expr(node);
     }
 
+    fn visit_module(&mut self, node: &Module) {
+        self.skip_prologue(module_prologue(node));
+        node.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, node: &Function) {
+        if let Some(body) = &node.body {
+            self.skip_prologue(function_prologue(&body.stmts));
+        }
+
+        self.with_scope(|this| node.visit_children_with(this));
+    }
+
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        if let BlockStmtOrExpr::BlockStmt(body) = &*node.body {
+            self.skip_prologue(function_prologue(&body.stmts));
+        }
+
+        self.with_scope(|this| node.visit_children_with(this));
+    }
+
+    fn visit_constructor(&mut self, node: &Constructor) {
+        if let Some(body) = &node.body {
+            self.skip_prologue(function_prologue(&body.stmts));
+        }
+
+        // the key is always `constructor`, `["constructor"]() {}` is a method
+        self.with_scope(|this| {
+            node.params.visit_with(this);
+            node.body.visit_with(this);
+        });
+    }
+
+    fn visit_super_prop_expr(&mut self, node: &SuperPropExpr) {
+        match &node.prop {
+            SuperProp::Ident(ident_name) => {
+                self.count_ident_name(ident_name, OccurrenceKind::Member);
+            }
+            SuperProp::Computed(computed_prop_name) => {
+                computed_prop_name.visit_with(self);
+            }
+        }
+    }
+
     fn visit_ident(&mut self, ident: &swc_ecma_ast::Ident) {
         self.collect_used_ident(ident);
 
@@ -308,27 +455,87 @@ impl Visit for IdentCollector {
 
             ident.visit_with(&mut matcher);
 
-            if !self.process_matcher_result(matcher.take_result()) {
-                return;
-            };
+            self.process_matcher_result(matcher.take_result());
         }
     }
 
     fn visit_lit(&mut self, lit: &Lit) {
         if let Lit::Str(lit) = lit {
-            self.count_lit(lit);
+            self.count_lit(lit, OccurrenceKind::Lit);
+        } else if let Lit::Regex(_) = lit {
+            // only the regexes read in place are constants, see `process_member_expr`
+        } else if let Some(key) = constant_key(lit) {
+            self.count_constant(lit, key);
         } else {
             lit.visit_children_with(self);
         }
     }
 
+    fn visit_tpl(&mut self, tpl: &Tpl) {
+        // `foo` => a
+        if let Some(value) = tpl_as_str(tpl) {
+            self.count_value(value, tpl.span, OccurrenceKind::Lit);
+            return;
+        }
+
+        for quasi in &tpl.quasis {
+            if let Some(value) = quasi.cooked.as_deref()
+                && !value.is_empty()
+            {
+                self.count_value(value, quasi.span, OccurrenceKind::Quasi);
+            }
+        }
+
+        tpl.exprs.visit_with(self);
+    }
+
+    fn visit_tagged_tpl(&mut self, node: &TaggedTpl) {
+        // the tag sees the raw strings, so they are left as they are
+        node.tag.visit_with(self);
+        node.tpl.exprs.visit_with(self);
+    }
+
+    // the module specifiers can not be replaced
+    fn visit_import_decl(&mut self, node: &ImportDecl) {
+        node.specifiers.visit_with(self);
+    }
+
+    fn visit_named_export(&mut self, node: &NamedExport) {
+        node.specifiers.visit_with(self);
+    }
+
+    fn visit_export_all(&mut self, _: &ExportAll) {}
+
+    fn visit_jsx_attr_value(&mut self, node: &JSXAttrValue) {
+        match node {
+            JSXAttrValue::Lit(Lit::Str(lit)) => {
+                self.count_lit(lit, OccurrenceKind::JsxAttr);
+            }
+            _ => {
+                node.visit_children_with(self);
+            }
+        }
+    }
+
+    fn visit_jsx_element_name(&mut self, node: &JSXElementName) {
+        match node {
+            // `<div>` is the string "div", `<Foo>` is a variable
+            JSXElementName::Ident(ident) if is_intrinsic_element(&ident.sym) => {
+                self.count_ident(ident, OccurrenceKind::JsxElement);
+            }
+            _ => {
+                node.visit_children_with(self);
+            }
+        }
+    }
+
     fn visit_prop_name(&mut self, prop_name: &PropName) {
         match prop_name {
             PropName::Ident(ident) => {
-                self.count_ident_name(ident);
+                self.count_ident_name(ident, OccurrenceKind::Key);
             }
             PropName::Str(str) => {
-                self.count_lit(str);
+                self.count_lit(str, OccurrenceKind::LitKey);
             }
             _ => {
                 prop_name.visit_children_with(self);
@@ -339,13 +546,39 @@ impl Visit for IdentCollector {
     fn visit_prop(&mut self, node: &Prop) {
         match node {
             Prop::Shorthand(ident) => {
-                self.count_ident(ident);
+                self.collect_used_ident(ident);
+                self.count_ident(ident, OccurrenceKind::Shorthand);
             }
             _ => {
                 node.visit_children_with(self);
             }
         }
     }
+
+    fn visit_object_pat_prop(&mut self, node: &ObjectPatProp) {
+        match node {
+            // `{ foo = 1 }` => `{ [a]: foo = 1 }`
+            ObjectPatProp::Assign(prop) => {
+                self.collect_used_ident(&prop.key.id);
+                self.count_ident(&prop.key.id, OccurrenceKind::Shorthand);
+
+                prop.value.visit_with(self);
+            }
+            _ => {
+                node.visit_children_with(self);
+            }
+        }
+    }
+}
+
+/// `/foo/.test(x)`, the members that do not change the regex
+fn is_regex_read(prop: &MemberProp) -> bool {
+    matches!(prop, MemberProp::Ident(ident) if ["test", "exec", "source", "flags"].contains(&ident.sym.as_str()))
+}
+
+/// like babel, `<div>` and `<my-element>` are strings
+fn is_intrinsic_element(name: &str) -> bool {
+    name.starts_with(|ch: char| ch.is_ascii_lowercase()) || name.contains('-')
 }
 
 #[derive(Debug, Default)]
@@ -450,18 +683,11 @@ impl From<Vec<String>> for Trie<String> {
 #[derive(Debug)]
 struct MemberMatcherResult {
     is_matched: bool,
-    ident_list: Vec<(String, Span)>,
+    ident_list: Vec<(String, Span, OccurrenceKind)>,
     match_result: MatchedResult,
     skip_spans: FxHashSet<Span>,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq, Copy)]
-enum MemberMatcherState {
-    Match,
-    #[default]
-    Visitor,
-}
-
 #[derive(Debug, Default, Clone, PartialEq, Eq, Copy)]
 enum CollectorMemberMatcherState {
     Match,
@@ -472,9 +698,8 @@ enum CollectorMemberMatcherState {
 #[derive(Debug)]
 struct MemberMatcher<'a, T: Debug> {
     pub trie: &'a Trie<T>,
-    pub paths: Vec<(String, Span)>,
-    pub state: MemberMatcherState,
-    pub ident_list: Vec<(String, Span)>,
+    pub paths: Vec<(String, Span, OccurrenceKind)>,
+    pub ident_list: Vec<(String, Span, OccurrenceKind)>,
     pub matched: bool,
     matchd_result: MatchedResult,
     skip_spans: FxHashSet<Span>,
@@ -485,7 +710,6 @@ impl<'a, T: Debug> MemberMatcher<'a, T> {
         Self {
             trie,
             paths: Default::default(),
-            state: Default::default(),
             ident_list: Default::default(),
             matched: false,
             matchd_result: None,
@@ -493,20 +717,6 @@ impl<'a, T: Debug> MemberMatcher<'a, T> {
         }
     }
 
-    fn with_state<F: FnOnce(&mut Self)>(&mut self, state: MemberMatcherState, f: F) {
-        let prev = self.state;
-        let prev_data =
-            (matches!(prev, MemberMatcherState::Match) && prev != state).then(|| self.paths.take());
-        self.state = state;
-
-        f(self);
-
-        self.state = prev;
-        if let Some(data) = prev_data {
-            self.paths = data;
-        }
-    }
-
     fn take_result(self) -> MemberMatcherResult {
         MemberMatcherResult {
             is_matched: self.matched,
@@ -529,16 +739,16 @@ impl<'a, T: Debug> MemberMatcher<'a, T> {
                     let (left, right) = paths.split_at_mut(pos);
 
                     self.skip_spans
-                        .extend(right.iter().rev().skip(1).map(|(_, span)| span));
+                        .extend(right.iter().rev().skip(1).map(|(_, span, _)| span));
 
                     self.ident_list.extend(
                         left.iter()
-                            .map(|(ident, span)| (ident.to_string(), *span))
+                            .map(|(ident, span, kind)| (ident.to_string(), *span, *kind))
                             .collect::<Vec<_>>(),
                     );
                 } else {
                     self.skip_spans
-                        .extend(paths.into_iter().map(|(_, span)| span));
+                        .extend(paths.into_iter().map(|(_, span, _)| span));
                 }
             }
 
@@ -549,63 +759,54 @@ impl<'a, T: Debug> MemberMatcher<'a, T> {
 
 impl Visit for MemberMatcher<'_, IgnoreWordTrieValue> {
     fn visit_member_expr(&mut self, node: &MemberExpr) {
-        self.with_state(MemberMatcherState::Match, |this| {
-            let mut is_end = false;
-            let mut is_ident_chain = false;
+        let (root, members) = member_chain(node);
 
-            match &node.prop {
+        // the outermost property first
+        for member in members.iter().rev() {
+            match &member.prop {
                 MemberProp::Ident(ident) => {
-                    this.paths.push((ident.sym.to_string(), ident.span));
+                    self.paths
+                        .push((ident.sym.to_string(), ident.span, OccurrenceKind::Member));
                 }
                 MemberProp::PrivateName(name) => {
-                    this.paths.push((name.name.to_string(), name.span));
+                    self.paths
+                        .push((name.name.to_string(), name.span, OccurrenceKind::Member));
                 }
                 MemberProp::Computed(computed_prop_name) => {
                     if let Expr::Lit(Lit::Str(lit)) = &*computed_prop_name.expr {
-                        this.paths.push((lit.value.to_string(), lit.span));
+                        self.paths
+                            .push((lit.value.to_string(), lit.span, OccurrenceKind::Lit));
                     }
                 }
             }
+        }
 
-            match &node.obj {
-                box Expr::Ident(ident) => {
-                    this.paths.push((ident.sym.to_string(), ident.span));
-                    is_end = true;
-                    is_ident_chain = true;
-                }
-                box Expr::Member(member) => {
-                    member.visit_with(this);
-                }
-                _ => {
-                    is_end = true;
-                    is_ident_chain = false;
-                }
-            }
+        let match_result = match root {
+            Expr::Ident(ident) => {
+                self.paths
+                    .push((ident.sym.to_string(), ident.span, OccurrenceKind::Member));
 
-            if is_end {
-                let match_result = is_ident_chain
-                    .then(|| {
-                        this.trie.query(
-                            this.paths
-                                .iter()
-                                .map(|(v, _)| v)
-                                .cloned()
-                                .rev()
-                                .collect::<Vec<_>>()
-                                .join("."),
-                        )
-                    })
-                    .flatten();
-
-                this.process_match_result(match_result);
-
-                this.paths.clear();
+                self.trie.query(
+                    self.paths
+                        .iter()
+                        .map(|(v, _, _)| v)
+                        .cloned()
+                        .rev()
+                        .collect::<Vec<_>>()
+                        .join("."),
+                )
             }
-        });
+            _ => None,
+        };
+
+        self.process_match_result(match_result);
+
+        self.paths.clear();
     }
 
     fn visit_ident(&mut self, node: &Ident) {
-        self.paths.push((node.sym.to_string(), node.span));
+        self.paths
+            .push((node.sym.to_string(), node.span, OccurrenceKind::Member));
         let match_result = self.trie.query(node.sym.to_string());
 
         self.process_match_result(match_result);
@@ -617,7 +818,7 @@ mod tests {
 
     use anyhow::Result;
     use std::sync::Arc;
-    use swc_common::{Globals, SourceMap};
+    use swc_common::{Globals, SourceMap, GLOBALS};
 
     use swc_ecma_parser::{EsSyntax, Syntax};
 
@@ -641,14 +842,16 @@ mod tests {
         let context = TransformContext {
             module_type: ModuleType::Javascript,
             options,
+            reserved: Default::default(),
             globals: Arc::new(globals),
+            cm,
         };
 
         let mut collector =
-            IdentCollector::new(unresolved_mark, top_level_mark).with_context(&context);
+            IdentCollector::new(top_level_mark, unresolved_mark).with_context(&context);
 
         // let mut matcher = MemberMatcher::new(&trie);
-        v.visit_with(&mut collector);
+        GLOBALS.set(&context.globals, || v.visit_with(&mut collector));
 
         Ok(collector)
     }
@@ -864,6 +1067,136 @@ require("./foo.js");
         Ok(())
     }
 
+    #[test]
+    fn occurrence_kind() -> Result<()> {
+        let code = r#"
+a.foo;
+a["foo"];
+({ foo: 1, "foo": 2, foo });
+`foo${a}`;
+"#;
+
+        let v = create_collector(code, TransformOption::default())?;
+
+        let (spans, occurrences) = &v.field["foo"];
+
+        assert_eq!(spans.len(), 6);
+        assert_eq!(occurrences.total(), 6);
+
+        // the affixes are only added by the transformer
+        for kind in &OccurrenceKind::ALL[..6] {
+            assert_eq!(occurrences.count(*kind), 1, "{:?}", kind);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn class_member() -> Result<()> {
+        let code = r#"
+class A extends B {
+    constructor() {
+        super();
+    }
+    foo() {}
+    static foo = 1;
+    get foo() {}
+    set foo(v) {}
+    bar() {
+        super.foo();
+        super["foo"];
+    }
+}
+a.constructor;
+"#;
+
+        let v = create_collector(code, TransformOption::default())?;
+
+        let (_, occurrences) = &v.field["foo"];
+
+        assert_eq!(occurrences.count(OccurrenceKind::Key), 4);
+        assert_eq!(occurrences.count(OccurrenceKind::Member), 1);
+        assert_eq!(occurrences.count(OccurrenceKind::Lit), 1);
+
+        // the key of the constructor is not counted
+        assert_eq!(v.field["constructor"].1.total(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn object_pat() -> Result<()> {
+        let code = r#"
+const { foo, bar: { foo: baz = 1 }, ...rest } = a;
+function f({ foo = 1 }, [{ foo: qux }]) {}
+({ foo } = a);
+"#;
+
+        let v = create_collector(code, TransformOption::default())?;
+
+        let (_, occurrences) = &v.field["foo"];
+
+        assert_eq!(occurrences.count(OccurrenceKind::Shorthand), 3);
+        assert_eq!(occurrences.count(OccurrenceKind::Key), 2);
+        assert!(v.used_ident.contains("foo"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn constants() -> Result<()> {
+        let code = r#"
+x & 0xffffffff;
+y & 0xffffffff;
+[10n, 4294967295, 4294967295.0];
+/foo/i.test(a);
+/foo/i.exec(a);
+/foo/i.lastIndex = 1;
+/foo/g.test(a);
+const r = /foo/i;
+f(/foo/i);
+[/foo/i];
+"#;
+
+        let v = create_collector(code, TransformOption::default())?;
+
+        let count = |key: &str| v.field[key].1.count(OccurrenceKind::Constant);
+
+        assert_eq!(count("0xffffffff"), 2);
+        assert_eq!(count("10n"), 1);
+        // written differently
+        assert_eq!(count("4294967295"), 1);
+        // stored, passed or changed, the regex may not be the same everywhere
+        assert_eq!(count("/foo/i"), 2);
+        assert!(!v.field.contains_key("/foo/g"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn directives() -> Result<()> {
+        let code = r#"
+"use strict";
+"use client";
+function foo() {
+    "use strict";
+    return "use strict";
+}
+const bar = () => {
+    "use client";
+    ("use strict");
+};
+"#;
+
+        let v = create_collector(code, TransformOption::default())?;
+
+        // only the strings out of the prologues
+        assert_eq!(v.field["use strict"].1.count(OccurrenceKind::Lit), 2);
+        assert!(!v.field.contains_key("use client"));
+
+        Ok(())
+    }
+
     #[test]
 
     fn used_ident() -> Result<()> {
@@ -891,4 +1224,35 @@ console.log(e);
 
         Ok(())
     }
+
+    #[test]
+    fn used_ident_in_scope() -> Result<()> {
+        let code = r#"
+function f(b) {
+    const c = 1;
+    return b + c;
+}
+
+function g(d) {
+    return obj.foo(d);
+}
+
+(() => {
+    let e = 1;
+    return () => obj.bar(e);
+})();
+
+foo();
+"#;
+
+        let v = create_collector(code, TransformOption::default())?;
+
+        let mut used_ident = v.used_ident.into_iter().collect::<Vec<_>>();
+        used_ident.sort();
+
+        // `b` and `c` can be reused, no string in `f` is replaced
+        assert_eq!(used_ident, vec!["d", "e", "f", "foo", "g", "obj"]);
+
+        Ok(())
+    }
 }
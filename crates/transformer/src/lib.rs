@@ -2,8 +2,13 @@
 #![feature(box_patterns)]
 #![feature(let_chains)]
 
+mod alias;
 mod collector;
+mod local_alias;
+mod mangle;
+mod private;
 mod replacer;
+mod shape;
 mod transformer;
 mod util;
 
@@ -16,12 +21,38 @@ extern crate napi_derive;
 
 #[cfg(feature = "node")]
 mod node {
+    use std::collections::HashMap;
+
     use super::*;
 
     #[napi(object)]
     pub struct TransformResult {
         pub content: String,
         pub map: Option<String>,
+        pub size_budget: Option<SizeBudgetResult>,
+        /// `"rawSizeNotReduced"` or `"compressedSizeNotReduced"`
+        pub fallback: Option<String>,
+        pub name_cache: Option<HashMap<String, String>>,
+        pub property_cache: Option<HashMap<String, String>>,
+    }
+
+    #[napi(object)]
+    pub struct SizeBudgetResult {
+        pub budget: u32,
+        pub size: u32,
+        pub met: bool,
+        pub remaining: i64,
+    }
+
+    impl From<transformer::SizeBudgetResult> for SizeBudgetResult {
+        fn from(result: transformer::SizeBudgetResult) -> Self {
+            SizeBudgetResult {
+                budget: result.budget as u32,
+                size: result.size as u32,
+                met: result.met,
+                remaining: result.remaining as i64,
+            }
+        }
     }
 
     impl From<transformer::TransformResult> for TransformResult {
@@ -29,6 +60,16 @@ mod node {
             TransformResult {
                 content: result.content,
                 map: result.map,
+                size_budget: result.size_budget.map(SizeBudgetResult::from),
+                fallback: result
+                    .fallback
+                    .map(|fallback| fallback.as_str().to_string()),
+                name_cache: result
+                    .name_cache
+                    .map(|name_cache| name_cache.into_iter().collect()),
+                property_cache: result
+                    .property_cache
+                    .map(|property_cache| property_cache.into_iter().collect()),
             }
         }
     }
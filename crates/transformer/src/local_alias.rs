@@ -0,0 +1,855 @@
+use itertools::Itertools;
+use omm_core::{select_local_alias, ChainAlias, CostStrategy, TokenAllocator};
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_common::{Span, DUMMY_SP};
+use swc_ecma_ast::{
+    ArrayLit, ArrowExpr, AssignExpr, AssignOp, AssignTarget, AwaitExpr, BinExpr, BinaryOp,
+    BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, CatchClause, Class, ClassDecl,
+    CondExpr, Constructor, Decl, DoWhileStmt, Expr, FnDecl, ForInStmt, ForOfStmt, ForStmt,
+    Function, Id, Ident, IfStmt, JSXElement, JSXFragment, LabeledStmt, MemberExpr, MemberProp,
+    Module, NewExpr, OptChainBase, OptChainExpr, Pat, SimpleAssignTarget, Stmt, SwitchStmt,
+    TaggedTpl, Tpl, TryStmt, UnaryExpr, UnaryOp, UpdateExpr, VarDecl, VarDeclKind, VarDeclarator,
+    WhileStmt, WithStmt, YieldExpr,
+};
+use swc_ecma_utils::find_pat_ids;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use crate::{alias::ChainReplacer, transformer::TransformContext, util::member_chain};
+
+/// `this` or a variable, the object a chain is read from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Root {
+    This,
+    Ident(Id),
+}
+
+/// `this.props.store` => (`this`, `["props", "store"]`)
+type ChainKey = (Root, Vec<String>);
+
+fn chain_text((root, props): &ChainKey) -> String {
+    let root = match root {
+        Root::This => "this",
+        Root::Ident((sym, _)) => sym.as_str(),
+    };
+
+    std::iter::once(root)
+        .chain(props.iter().map(|prop| prop.as_str()))
+        .join(".")
+}
+
+///
+/// The root of `this.props.store` and the members reading each of its prefixes, the shortest
+/// first, only given identifier properties.
+///
+/// The members are walked by [`member_chain`], like the paths of the ignore words. Reading
+/// a prefix is assumed to run no code, a getter with a side effect would run fewer times
+/// once the chain is aliased.
+///
+fn chain_of(member: &MemberExpr) -> Option<(Root, Vec<(String, &MemberExpr)>)> {
+    let (root, members) = member_chain(member);
+
+    let root = match root {
+        Expr::This(_) => Root::This,
+        Expr::Ident(ident) => Root::Ident(ident.to_id()),
+        _ => return None,
+    };
+
+    let members = members
+        .into_iter()
+        .map(|member| match &member.prop {
+            MemberProp::Ident(prop) => Some((prop.sym.to_string(), member)),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some((root, members))
+}
+
+/// What a write may change.
+#[derive(Debug, Clone)]
+enum Write {
+    ///
+    /// a call, or anything else running code that is not seen, e.g. `obj.foo = 1` runs
+    /// a setter and `obj + ""` runs `valueOf`
+    ///
+    Any,
+    /// `foo = 1`
+    Ident(Id),
+}
+
+impl Write {
+    fn affects(&self, (root, _): &ChainKey) -> bool {
+        match self {
+            Write::Any => true,
+            Write::Ident(id) => matches!(root, Root::Ident(root) if root == id),
+        }
+    }
+}
+
+/// `1` or `"foo"`, converting it to a number or a string runs no code
+fn is_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Lit(_))
+}
+
+/// whether `op` converts its operands, or runs a `Symbol.hasInstance` or a proxy trap
+fn may_coerce(op: BinaryOp) -> bool {
+    !matches!(
+        op,
+        BinaryOp::EqEqEq
+            | BinaryOp::NotEqEq
+            | BinaryOp::LogicalAnd
+            | BinaryOp::LogicalOr
+            | BinaryOp::NullishCoalescing
+    )
+}
+
+#[derive(Debug)]
+struct ChainSites {
+    /// the statement of the body reading the chain first
+    stmt: usize,
+    /// the first member reading the chain, the initializer of the alias
+    init: MemberExpr,
+    spans: Vec<Span>,
+    /// written since it was read
+    stale: bool,
+    valid: bool,
+}
+
+/// The `let`, `const`, `class`, function and `catch` names declared in a statement.
+#[derive(Default)]
+struct ScopedNames {
+    names: FxHashSet<Id>,
+}
+
+impl Visit for ScopedNames {
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+
+    fn visit_var_decl(&mut self, node: &VarDecl) {
+        if node.kind != VarDeclKind::Var {
+            self.names.extend(find_pat_ids::<_, Id>(&node.decls));
+        }
+
+        node.visit_children_with(self);
+    }
+
+    fn visit_class_decl(&mut self, node: &ClassDecl) {
+        self.names.insert(node.ident.to_id());
+    }
+
+    fn visit_fn_decl(&mut self, node: &FnDecl) {
+        self.names.insert(node.ident.to_id());
+    }
+
+    fn visit_catch_clause(&mut self, node: &CatchClause) {
+        self.names.extend(find_pat_ids::<_, Id>(&node.param));
+        node.body.visit_with(self);
+    }
+}
+
+///
+/// The scoped names declared in a function body, and the statement of the body declaring them,
+/// `None` when they are declared in a nested block.
+///
+fn scoped_names(stmts: &[Stmt]) -> FxHashMap<Id, Option<usize>> {
+    let mut scoped = FxHashMap::default();
+
+    for (index, stmt) in stmts.iter().enumerate() {
+        let mut names = ScopedNames::default();
+
+        stmt.visit_with(&mut names);
+
+        let declared = matches!(stmt, Stmt::Decl(_)).then_some(index);
+
+        scoped.extend(names.names.into_iter().map(|id| (id, declared)));
+    }
+
+    scoped
+}
+
+///
+/// Walks the statements of a function body in the order they run, and collects the reads
+/// of each member chain until the chain, or one of its objects, may be written.
+///
+/// Reading a property is assumed to have no side effect, anything else running code that
+/// is not seen, e.g. a call, `await` or a loop running again, may write every chain.
+///
+struct ChainWalker<'a> {
+    scoped: &'a FxHashMap<Id, Option<usize>>,
+    stmt: usize,
+    /// inside a branch that may not run
+    depth: usize,
+    chains: FxHashMap<ChainKey, ChainSites>,
+    /// the writes of the current statement
+    writes: Vec<Write>,
+    /// `with` changes what the names refer to
+    dynamic: bool,
+}
+
+impl<'a> ChainWalker<'a> {
+    fn new(scoped: &'a FxHashMap<Id, Option<usize>>) -> Self {
+        Self {
+            scoped,
+            stmt: 0,
+            depth: 0,
+            chains: Default::default(),
+            writes: Default::default(),
+            dynamic: false,
+        }
+    }
+
+    fn conditional<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.depth += 1;
+        f(self);
+        self.depth -= 1;
+    }
+
+    fn write(&mut self, write: Write) {
+        for (key, sites) in &mut self.chains {
+            if write.affects(key) {
+                sites.stale = true;
+            }
+        }
+
+        self.writes.push(write);
+    }
+
+    /// whether `member` is a chain, the callee of a call is not a read of the whole chain
+    fn read(&mut self, member: &MemberExpr, called: bool) -> bool {
+        let Some((root, members)) = chain_of(member) else {
+            return false;
+        };
+
+        let len = members.len() - usize::from(called);
+        let mut props = Vec::with_capacity(len);
+
+        for (prop, member) in members.into_iter().take(len) {
+            props.push(prop);
+            self.read_chain((root.clone(), props.clone()), member);
+        }
+
+        true
+    }
+
+    fn read_chain(&mut self, key: ChainKey, member: &MemberExpr) {
+        if member.span.is_dummy() {
+            return;
+        }
+
+        if let Some(sites) = self.chains.get_mut(&key) {
+            if sites.stale {
+                sites.valid = false;
+            } else {
+                sites.spans.push(member.span);
+            }
+
+            return;
+        }
+
+        // the alias is declared before the statement, so the first read must run with it
+        let declared = match &key.0 {
+            Root::This => false,
+            Root::Ident(id) => self
+                .scoped
+                .get(id)
+                .is_some_and(|stmt| stmt.is_none_or(|stmt| stmt >= self.stmt)),
+        };
+        let valid =
+            self.depth == 0 && !declared && !self.writes.iter().any(|write| write.affects(&key));
+
+        self.chains.insert(
+            key,
+            ChainSites {
+                stmt: self.stmt,
+                init: member.clone(),
+                spans: vec![member.span],
+                stale: false,
+                valid,
+            },
+        );
+    }
+
+    /// `this.props.onChange` of `this.props.onChange()` is called with `this.props`
+    fn visit_callee(&mut self, callee: &Expr) {
+        match callee {
+            Expr::Paren(paren) => self.visit_callee(&paren.expr),
+            Expr::Member(member) if self.read(member, true) => {}
+            Expr::Member(member) => member.visit_children_with(self),
+            callee => callee.visit_with(self),
+        }
+    }
+
+    /// `obj` and `foo` of `obj[foo] = 1`
+    fn visit_target(&mut self, member: &MemberExpr) {
+        member.obj.visit_with(self);
+        member.prop.visit_with(self);
+    }
+
+    /// the writes of `node`, they happen before it runs again
+    fn visit_loop<N: for<'b> VisitWith<ChainWalker<'b>>>(&mut self, node: &N) {
+        let mut walker = ChainWalker::new(self.scoped);
+
+        node.visit_children_with(&mut walker);
+
+        for write in walker.writes {
+            self.write(write);
+        }
+
+        self.dynamic |= walker.dynamic;
+        self.conditional(|this| node.visit_children_with(this));
+    }
+}
+
+impl Visit for ChainWalker<'_> {
+    // run later, or never
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    // the static blocks and computed keys run code
+    fn visit_class(&mut self, _: &Class) {
+        self.write(Write::Any);
+    }
+
+    fn visit_member_expr(&mut self, node: &MemberExpr) {
+        if !self.read(node, false) {
+            node.visit_children_with(self);
+        }
+    }
+
+    fn visit_call_expr(&mut self, node: &CallExpr) {
+        match &node.callee {
+            Callee::Expr(callee) => self.visit_callee(callee),
+            callee => callee.visit_with(self),
+        }
+
+        node.args.visit_with(self);
+        self.write(Write::Any);
+    }
+
+    fn visit_new_expr(&mut self, node: &NewExpr) {
+        node.visit_children_with(self);
+        self.write(Write::Any);
+    }
+
+    // the tag takes the values as they are
+    fn visit_tagged_tpl(&mut self, node: &TaggedTpl) {
+        self.visit_callee(&node.tag);
+        node.tpl.exprs.visit_with(self);
+        self.write(Write::Any);
+    }
+
+    // `${obj}` runs `toString`
+    fn visit_tpl(&mut self, node: &Tpl) {
+        for expr in &node.exprs {
+            expr.visit_with(self);
+
+            if !is_literal(expr) {
+                self.write(Write::Any);
+            }
+        }
+    }
+
+    fn visit_opt_chain_expr(&mut self, node: &OptChainExpr) {
+        // the rest of the chain is skipped when the object is nullish
+        self.conditional(|this| match &*node.base {
+            OptChainBase::Member(member) => {
+                member.obj.visit_with(this);
+                member.prop.visit_with(this);
+            }
+            OptChainBase::Call(call) => {
+                this.visit_callee(&call.callee);
+                call.args.visit_with(this);
+                this.write(Write::Any);
+            }
+        });
+    }
+
+    fn visit_await_expr(&mut self, node: &AwaitExpr) {
+        node.visit_children_with(self);
+        self.write(Write::Any);
+    }
+
+    fn visit_yield_expr(&mut self, node: &YieldExpr) {
+        node.visit_children_with(self);
+        self.write(Write::Any);
+    }
+
+    // `<A />` is a call
+    fn visit_jsx_element(&mut self, node: &JSXElement) {
+        node.visit_children_with(self);
+        self.write(Write::Any);
+    }
+
+    fn visit_jsx_fragment(&mut self, node: &JSXFragment) {
+        node.visit_children_with(self);
+        self.write(Write::Any);
+    }
+
+    // `[...items]` runs the iterator
+    fn visit_array_lit(&mut self, node: &ArrayLit) {
+        node.visit_children_with(self);
+
+        if node
+            .elems
+            .iter()
+            .flatten()
+            .any(|elem| elem.spread.is_some())
+        {
+            self.write(Write::Any);
+        }
+    }
+
+    fn visit_assign_expr(&mut self, node: &AssignExpr) {
+        let write = match &node.left {
+            // a setter or a proxy may write anything
+            AssignTarget::Simple(SimpleAssignTarget::Member(member)) => {
+                self.visit_target(member);
+                Some(Write::Any)
+            }
+            AssignTarget::Simple(SimpleAssignTarget::Ident(binding)) => {
+                Some(Write::Ident(binding.id.to_id()))
+            }
+            _ => None,
+        };
+
+        if node.op.may_short_circuit() {
+            self.conditional(|this| node.right.visit_with(this));
+        } else {
+            node.right.visit_with(self);
+        }
+
+        // `foo += 1` converts `foo`
+        if node.op != AssignOp::Assign && !node.op.may_short_circuit() {
+            self.write(Write::Any);
+        }
+
+        match write {
+            Some(write) => self.write(write),
+            // `[a, b = c] = d`, the defaults and the iterator run after the right side
+            None => {
+                self.conditional(|this| node.left.visit_with(this));
+                self.write(Write::Any);
+            }
+        }
+    }
+
+    fn visit_update_expr(&mut self, node: &UpdateExpr) {
+        self.visit_written(&node.arg);
+    }
+
+    fn visit_unary_expr(&mut self, node: &UnaryExpr) {
+        match node.op {
+            UnaryOp::Delete => self.visit_written(&node.arg),
+            // `-obj` runs `valueOf`
+            UnaryOp::Plus | UnaryOp::Minus | UnaryOp::Tilde => {
+                node.arg.visit_with(self);
+
+                if !is_literal(&node.arg) {
+                    self.write(Write::Any);
+                }
+            }
+            _ => node.arg.visit_with(self),
+        }
+    }
+
+    fn visit_var_declarator(&mut self, node: &VarDeclarator) {
+        node.init.visit_with(self);
+
+        match &node.name {
+            Pat::Ident(binding) => self.write(Write::Ident(binding.id.to_id())),
+            name => {
+                self.conditional(|this| name.visit_with(this));
+                self.write(Write::Any);
+            }
+        }
+    }
+
+    fn visit_bin_expr(&mut self, node: &BinExpr) {
+        node.left.visit_with(self);
+
+        if node.op.may_short_circuit() {
+            self.conditional(|this| node.right.visit_with(this));
+        } else {
+            node.right.visit_with(self);
+        }
+
+        if may_coerce(node.op) && !(is_literal(&node.left) && is_literal(&node.right)) {
+            self.write(Write::Any);
+        }
+    }
+
+    fn visit_cond_expr(&mut self, node: &CondExpr) {
+        node.test.visit_with(self);
+        self.conditional(|this| {
+            node.cons.visit_with(this);
+            node.alt.visit_with(this);
+        });
+    }
+
+    fn visit_if_stmt(&mut self, node: &IfStmt) {
+        node.test.visit_with(self);
+        self.conditional(|this| {
+            node.cons.visit_with(this);
+            node.alt.visit_with(this);
+        });
+    }
+
+    fn visit_switch_stmt(&mut self, node: &SwitchStmt) {
+        node.discriminant.visit_with(self);
+        self.conditional(|this| node.cases.visit_with(this));
+    }
+
+    // a throw, `break` or `return` skips the rest of it
+    fn visit_try_stmt(&mut self, node: &TryStmt) {
+        self.conditional(|this| node.visit_children_with(this));
+    }
+
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        self.conditional(|this| node.visit_children_with(this));
+    }
+
+    fn visit_labeled_stmt(&mut self, node: &LabeledStmt) {
+        self.conditional(|this| node.visit_children_with(this));
+    }
+
+    fn visit_while_stmt(&mut self, node: &WhileStmt) {
+        self.visit_loop(node);
+    }
+
+    fn visit_do_while_stmt(&mut self, node: &DoWhileStmt) {
+        self.visit_loop(node);
+    }
+
+    fn visit_for_stmt(&mut self, node: &ForStmt) {
+        self.visit_loop(node);
+    }
+
+    fn visit_for_in_stmt(&mut self, node: &ForInStmt) {
+        self.visit_loop(node);
+    }
+
+    // the iterator runs for each item
+    fn visit_for_of_stmt(&mut self, node: &ForOfStmt) {
+        self.write(Write::Any);
+        self.visit_loop(node);
+    }
+
+    fn visit_with_stmt(&mut self, _: &WithStmt) {
+        self.dynamic = true;
+    }
+}
+
+impl ChainWalker<'_> {
+    ///
+    /// `obj.foo` of `obj.foo++` or `delete obj.foo`, a setter, `valueOf` or a proxy may write
+    /// anything
+    ///
+    fn visit_written(&mut self, arg: &Expr) {
+        match arg {
+            Expr::Member(member) => self.visit_target(member),
+            Expr::Ident(_) => {}
+            arg => arg.visit_with(self),
+        }
+
+        self.write(Write::Any);
+    }
+}
+
+// const a = this.props.store;
+fn alias_decl(name: &str, init: MemberExpr) -> Stmt {
+    Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        ctxt: Default::default(),
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: name.into(),
+                type_ann: None,
+            }),
+            init: Some(Box::new(Expr::Member(init))),
+            definite: false,
+        }],
+    })))
+}
+
+/// The chains of a function body that may be aliased, `None` when it has a `with`.
+fn aliasable_chains(stmts: &[Stmt]) -> Option<FxHashMap<ChainAlias, ChainSites>> {
+    let scoped = scoped_names(stmts);
+    let mut walker = ChainWalker::new(&scoped);
+
+    for (index, stmt) in stmts.iter().enumerate() {
+        walker.stmt = index;
+        walker.writes.clear();
+        stmt.visit_with(&mut walker);
+    }
+
+    if walker.dynamic {
+        return None;
+    }
+
+    // `a.b` of two different `a` can not be told apart
+    let texts = walker.chains.keys().map(chain_text).counts();
+
+    Some(
+        walker
+            .chains
+            .into_iter()
+            .filter(|(key, sites)| sites.valid && texts[&chain_text(key)] == 1)
+            .map(|(key, sites)| (ChainAlias::new(&chain_text(&key), false), sites))
+            .collect(),
+    )
+}
+
+///
+/// Aliases the chains of `stmts` one at a time, the one saving the most first, until none
+/// is worth it.
+///
+fn alias_body(stmts: &mut Vec<Stmt>, allocator: &TokenAllocator, strategy: &CostStrategy) {
+    let mut allocator = allocator.clone();
+
+    loop {
+        let Some(mut candidates) = aliasable_chains(stmts) else {
+            return;
+        };
+
+        let Some(name) = allocator.clone().alloc() else {
+            return;
+        };
+
+        let Some(selected) = select_local_alias(
+            candidates
+                .iter()
+                .map(|(alias, sites)| (alias.clone(), sites.spans.len()))
+                .collect(),
+            &name,
+            strategy,
+        ) else {
+            return;
+        };
+
+        let name = allocator.alloc().unwrap_or_else(|| unreachable!());
+        let sites = candidates
+            .remove(&selected)
+            .unwrap_or_else(|| unreachable!());
+
+        stmts.visit_mut_with(&mut ChainReplacer {
+            sites: sites
+                .spans
+                .iter()
+                .map(|span| (*span, name.clone()))
+                .collect(),
+        });
+        stmts.insert(sites.stmt, alias_decl(&name, sites.init));
+    }
+}
+
+/// every name of the module, the aliases can not take them
+#[derive(Default)]
+struct IdentCollector {
+    idents: FxHashSet<String>,
+}
+
+impl Visit for IdentCollector {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.idents.insert(ident.sym.to_string());
+    }
+}
+
+struct LocalChainAliaser<'a> {
+    allocator: &'a TokenAllocator,
+    strategy: &'a CostStrategy,
+}
+
+impl VisitMut for LocalChainAliaser<'_> {
+    fn visit_mut_function(&mut self, node: &mut Function) {
+        if let Some(body) = &mut node.body {
+            alias_body(&mut body.stmts, self.allocator, self.strategy);
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_arrow_expr(&mut self, node: &mut ArrowExpr) {
+        if let BlockStmtOrExpr::BlockStmt(body) = &mut *node.body {
+            alias_body(&mut body.stmts, self.allocator, self.strategy);
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_constructor(&mut self, node: &mut Constructor) {
+        if let Some(body) = &mut node.body {
+            alias_body(&mut body.stmts, self.allocator, self.strategy);
+        }
+
+        node.visit_mut_children_with(self);
+    }
+}
+
+///
+/// Aliases the member chains read repeatedly in a function with a `const` declared in it,
+/// when nothing between the reads may write them.
+///
+/// ```unknown
+/// function f() { use(this.props.store.state.user.name, this.props.store.state.user.id) }
+///
+/// function f() { const a = this.props.store.state.user; use(a.name, a.id) }
+/// ```
+///
+/// The alias is declared before the statement reading the chain first, and that read must
+/// always run, so the chain is never read when it was not.
+///
+pub fn alias_local_chains(module: &mut Module, context: &TransformContext) {
+    let mut collector = IdentCollector::default();
+
+    module.visit_with(&mut collector);
+
+    let mut allocator = TokenAllocator::new().with_reserved(context.reserved.clone());
+
+    allocator.extends(collector.idents);
+
+    module.visit_mut_with(&mut LocalChainAliaser {
+        allocator: &allocator,
+        strategy: &context.options.cost_strategy,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use swc_common::{FileName, Globals, SourceMap};
+    use swc_ecma_ast::ModuleItem;
+    use swc_ecma_parser::{EsSyntax, Syntax};
+
+    use crate::util::{resolve_module_mark, script::parse};
+
+    use super::*;
+
+    /// the chains of the body of `function f`, read more than once, that may be aliased
+    fn chains(code: &str) -> Result<Vec<(String, usize)>> {
+        let cm = Arc::new(SourceMap::default());
+        let source_file_name = Arc::new(FileName::Custom("test.js".to_string()));
+        let source_file = cm.new_source_file(source_file_name, code.to_string());
+        let mut module = parse(&source_file, Syntax::Es(EsSyntax::default()))?;
+
+        resolve_module_mark(&mut module, false, &Globals::default());
+
+        let Some(ModuleItem::Stmt(Stmt::Decl(Decl::Fn(decl)))) = module.body.first() else {
+            unreachable!()
+        };
+        let stmts = &decl.function.body.as_ref().unwrap().stmts;
+
+        Ok(aliasable_chains(stmts)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, sites)| sites.spans.len() > 1)
+            .map(|(alias, sites)| (alias.chain, sites.spans.len()))
+            .sorted()
+            .collect())
+    }
+
+    fn expected(chains: &[(&str, usize)]) -> Vec<(String, usize)> {
+        chains
+            .iter()
+            .map(|(chain, count)| (chain.to_string(), *count))
+            .collect()
+    }
+
+    #[test]
+    fn loops() -> Result<()> {
+        // the loop only writes `s`
+        let v = chains("function f(o) { let s = o.p.q; while (s) { s = o.p.q } return o.p.q; }")?;
+
+        assert_eq!(v, expected(&[("o.p", 3), ("o.p.q", 3)]));
+
+        // the write runs before the reads of the next round
+        let v = chains(
+            "function f(o) { let s = o.p.q; while (s) { s = o.p.q; o.p = s } return o.p.q; }",
+        )?;
+
+        assert_eq!(v, vec![]);
+
+        // `i++` may run `valueOf`
+        let v = chains("function f(o) { for (let i = 0; i < 2; i++) { use(o.p.q, o.p.q) } }")?;
+
+        assert_eq!(v, vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn short_circuits() -> Result<()> {
+        // the first read may not run
+        let v = chains("function f(o) { const x = o.a || o.p.q; return [o.p.q, o.p.q]; }")?;
+
+        assert_eq!(v, vec![]);
+
+        let v = chains("function f(o) { const x = o.p.q || o.a; return [o.p.q, o.p.q]; }")?;
+
+        assert_eq!(v, expected(&[("o.p", 3), ("o.p.q", 3)]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn setter_writes() -> Result<()> {
+        // `value` may be a setter replacing `o.s`
+        let v = chains("function f(o) { const x = o.s.u.n; o.value = 1; return [o.s.u, o.s.u]; }")?;
+
+        assert_eq!(v, vec![]);
+
+        let v = chains("function f(o) { const x = o.s.u.n; delete o.value; return [o.s.u]; }")?;
+
+        assert_eq!(v, vec![]);
+
+        // a local variable has no setter
+        let v = chains("function f(o, v) { const x = o.s.u.n; v = 1; return [o.s.u, o.s.u]; }")?;
+
+        assert_eq!(v, expected(&[("o.s", 3), ("o.s.u", 3)]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn coercions() -> Result<()> {
+        // `toString` and `valueOf` may replace `o.s`
+        for code in [
+            "function f(o) { const x = o.s.u.n + \"\"; return [o.s.u, o.s.u]; }",
+            "function f(o) { const x = `${o.s.u.n}`; return [o.s.u, o.s.u]; }",
+            "function f(o) { const x = o.s.u.n < 1; return [o.s.u, o.s.u]; }",
+            "function f(o) { const x = -o.s.u.n; return [o.s.u, o.s.u]; }",
+            "function f(o, v) { const x = o.s.u.n; v += 1; return [o.s.u, o.s.u]; }",
+        ] {
+            assert_eq!(chains(code)?, vec![], "{}", code);
+        }
+
+        // nothing to convert
+        let v = chains("function f(o) { const x = o.s.u.n === 1; return [o.s.u, o.s.u]; }")?;
+
+        assert_eq!(v, expected(&[("o.s", 3), ("o.s.u", 3)]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn names_declared_in_the_same_statement() -> Result<()> {
+        // `a` is not declared yet where the alias would be
+        let v = chains("function f() { const a = {}, b = a.x.y; return [a.x.y, a.x.y]; }")?;
+
+        assert_eq!(v, vec![]);
+
+        let v = chains("function f() { const a = {}; const b = a.x.y; return [a.x.y, a.x.y]; }")?;
+
+        assert_eq!(v, expected(&[("a.x", 3), ("a.x.y", 3)]));
+
+        Ok(())
+    }
+}
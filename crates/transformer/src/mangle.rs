@@ -0,0 +1,322 @@
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use omm_core::{OccurrenceKind, TokenAllocator};
+use regex::Regex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_common::Span;
+use swc_ecma_ast::{
+    AssignPat, BinExpr, BinaryOp, ComputedPropName, Expr, IdentName, JSXAttrName, JSXMemberExpr,
+    KeyValuePatProp, KeyValueProp, Lit, Module, ObjectPatProp, Pat, Prop, PropName, Str,
+};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use crate::{
+    collector::IdentCollector,
+    replacer::KeyNormalizer,
+    transformer::{IgnoreWord, StringLitOptions, TransformContext},
+    util::resolve_module_mark,
+};
+
+/// The kinds naming a property, the others are values that may be read as a property.
+const PROPERTY_KINDS: [OccurrenceKind; 4] = [
+    OccurrenceKind::Member,
+    OccurrenceKind::Key,
+    OccurrenceKind::LitKey,
+    OccurrenceKind::Shorthand,
+];
+
+/// The property names the collector does not see, `<A foo={1}>` and `<A.foo>`.
+#[derive(Default)]
+struct JsxNameCollector {
+    names: FxHashSet<String>,
+}
+
+impl Visit for JsxNameCollector {
+    fn visit_jsx_attr_name(&mut self, node: &JSXAttrName) {
+        if let JSXAttrName::Ident(ident) = node {
+            self.names.insert(ident.sym.to_string());
+        }
+    }
+
+    fn visit_jsx_member_expr(&mut self, node: &JSXMemberExpr) {
+        self.names.insert(node.prop.sym.to_string());
+        node.obj.visit_with(self);
+    }
+}
+
+/// The strings naming a property, `"foo" in obj`, `obj["foo"]` and `{ ["foo"]: 1 }`.
+#[derive(Default)]
+struct PropertyStrCollector {
+    spans: FxHashSet<Span>,
+}
+
+impl PropertyStrCollector {
+    fn add(&mut self, expr: &Expr) {
+        if let Expr::Lit(Lit::Str(str)) = expr {
+            self.spans.insert(str.span);
+        }
+    }
+}
+
+impl Visit for PropertyStrCollector {
+    fn visit_bin_expr(&mut self, node: &BinExpr) {
+        if node.op == BinaryOp::In {
+            self.add(&node.left);
+        }
+
+        node.visit_children_with(self);
+    }
+
+    // `obj["foo"]`, `super["foo"]` and `{ ["foo"]: 1 }`
+    fn visit_computed_prop_name(&mut self, node: &ComputedPropName) {
+        self.add(&node.expr);
+        node.visit_children_with(self);
+    }
+}
+
+/// Renames the properties, keyed by the span of each occurrence.
+struct PropertyRenamer {
+    sites: FxHashMap<Span, String>,
+}
+
+impl VisitMut for PropertyRenamer {
+    // `obj.foo`, `super.foo` and `{ foo: 1 }`
+    fn visit_mut_ident_name(&mut self, node: &mut IdentName) {
+        if let Some(name) = self.sites.get(&node.span) {
+            node.sym = name.as_str().into();
+        }
+    }
+
+    fn visit_mut_prop_name(&mut self, node: &mut PropName) {
+        // `{ "foo": 1 }` => `{ a: 1 }`
+        if let PropName::Str(str) = node
+            && let Some(name) = self.sites.get(&str.span)
+        {
+            *node = PropName::Ident(IdentName::new(name.as_str().into(), str.span));
+            return;
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    // `"foo" in obj`
+    fn visit_mut_str(&mut self, node: &mut Str) {
+        if let Some(name) = self.sites.get(&node.span) {
+            *node = Str {
+                span: node.span,
+                value: name.as_str().into(),
+                raw: None,
+            };
+        }
+    }
+
+    fn visit_mut_prop(&mut self, node: &mut Prop) {
+        // `{ foo }` => `{ a: foo }`
+        if let Prop::Shorthand(ident) = node
+            && let Some(name) = self.sites.get(&ident.span)
+        {
+            *node = Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName::new(name.as_str().into(), ident.span)),
+                value: Box::new(Expr::Ident(ident.clone())),
+            });
+            return;
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_object_pat_prop(&mut self, node: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(prop) = node
+            && let Some(name) = self.sites.get(&prop.key.id.span)
+        {
+            let binding = Box::new(Pat::Ident(prop.key.clone()));
+
+            // `{ foo = 1 }` => `{ a: foo = 1 }`
+            let value = match prop.value.take() {
+                Some(right) => Box::new(Pat::Assign(AssignPat {
+                    span: prop.span,
+                    left: binding,
+                    right,
+                })),
+                None => binding,
+            };
+
+            *node = ObjectPatProp::KeyValue(KeyValuePatProp {
+                key: PropName::Ident(IdentName::new(name.as_str().into(), prop.key.id.span)),
+                value,
+            });
+        }
+
+        node.visit_mut_children_with(self);
+    }
+}
+
+/// `require.async` and `"m1"` of the ignore words, every segment of a path is reserved.
+fn ignored_names(ignore_words: &[IgnoreWord]) -> FxHashSet<String> {
+    ignore_words
+        .iter()
+        .flat_map(|word| match word {
+            IgnoreWord::StringLit(StringLitOptions { content }) => vec![content.to_string()],
+            word => word
+                .path()
+                .map(|path| path.split('.').map(str::to_string).collect())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+///
+/// Renames the properties matching `regex` across the module, returns the renamed
+/// properties and their new names.
+///
+/// ```unknown
+/// class A { _cache = {}; get(k) { return this._cache[k] } }
+/// const { _cache } = new A();
+///
+/// class A { a = {}; get(k) { return this.a[k] } }
+/// const { a: _cache } = new A();
+/// ```
+///
+/// `obj["_foo"]` is renamed like `obj._foo`. A property is left as it is when it also
+/// appears as a string, e.g. `"_foo" in obj`, when it is reserved, or when it names a jsx
+/// attribute. The name cache overrides this, the chunks sharing it rename a property alike,
+/// so the properties left as they are are returned as themselves. A cached property is then
+/// renamed in the strings naming it too, but not in the other strings, e.g. `log("_foo")`.
+///
+/// It fails when a property of the module can not take its cached name, e.g. another
+/// property left as it is already has that name.
+///
+pub fn mangle_properties(
+    module: &mut Module,
+    context: &TransformContext,
+    regex: &Regex,
+) -> Result<FxHashMap<String, String>> {
+    let Some(option) = &context.options.mangle_properties else {
+        return Ok(Default::default());
+    };
+
+    // `obj["_foo"]` is `obj._foo`
+    module.visit_mut_with(&mut KeyNormalizer {
+        ascii_only: context.options.ascii_only,
+    });
+
+    let (unresolved_mark, top_level_mark) = resolve_module_mark(
+        module,
+        context.module_type.is_typescript(),
+        &context.globals,
+    );
+
+    let mut collector = IdentCollector::new(top_level_mark, unresolved_mark).with_context(context);
+
+    module.visit_with(&mut collector);
+
+    let mut jsx_names = JsxNameCollector::default();
+
+    module.visit_with(&mut jsx_names);
+
+    let mut reserved = ignored_names(&context.options.ignore_words);
+
+    reserved.extend(option.reserved.iter().cloned());
+    reserved.extend(jsx_names.names);
+
+    let cache = option.name_cache.clone().unwrap_or_default();
+
+    let (mangled, kept): (Vec<_>, Vec<_>) =
+        collector
+            .field
+            .iter()
+            .partition(|(property, (_, occurrences))| {
+                if !regex.is_match(property) || option.reserved.contains(property) {
+                    return false;
+                }
+
+                // another chunk decided it already, `_foo: _foo` is kept
+                if let Some(name) = cache.get(property.as_str()) {
+                    return name != property.as_str();
+                }
+
+                !reserved.contains(property.as_str())
+                    && occurrences.total()
+                        == PROPERTY_KINDS
+                            .iter()
+                            .map(|kind| occurrences.count(*kind))
+                            .sum::<usize>()
+            });
+
+    // the other chunks keep them too
+    let mut names = kept
+        .iter()
+        .filter(|(property, _)| regex.is_match(property) && !option.reserved.contains(property))
+        .map(|(property, _)| (property.to_string(), property.to_string()))
+        .collect::<FxHashMap<_, _>>();
+
+    if mangled.is_empty() {
+        return Ok(names);
+    }
+
+    // a new name never takes a property left as it is
+    let mut allocator = TokenAllocator::new();
+
+    allocator.extends(
+        kept.iter()
+            .map(|(property, _)| property.to_string())
+            .chain(reserved)
+            .collect(),
+    );
+
+    let mut allocator = allocator.with_cache(cache.clone());
+
+    // the other chunks use the cached name, a new one would break them
+    for (property, _) in &mangled {
+        if let Some(name) = cache.get(property.as_str())
+            && allocator.cached(property) != Some(name.as_str())
+        {
+            bail!("the property `{property}` can not take its cached name `{name}`");
+        }
+    }
+
+    // the most used property takes the shortest name
+    let renamed = mangled
+        .iter()
+        .sorted_by_key(|(property, (_, occurrences))| {
+            (usize::MAX - occurrences.total(), property.to_string())
+        })
+        .map(|(property, _)| {
+            // only the properties of the chunk are taken, the names never run out
+            let name = allocator
+                .alloc_for(property)
+                .unwrap_or_else(|| unreachable!());
+
+            (property.to_string(), name)
+        })
+        .collect::<FxHashMap<_, _>>();
+
+    // a cached property may also be a string naming it here, e.g. `"_foo" in obj`, the other
+    // strings are values and are left as they are
+    let mut property_strs = PropertyStrCollector::default();
+
+    module.visit_with(&mut property_strs);
+
+    let mut renamer = PropertyRenamer {
+        sites: mangled
+            .iter()
+            .flat_map(|(property, (spans, _))| {
+                let name = &renamed[property.as_str()];
+
+                spans
+                    .iter()
+                    .filter(|(span, kind)| {
+                        PROPERTY_KINDS.contains(kind)
+                            || (**kind == OccurrenceKind::Lit && property_strs.spans.contains(span))
+                    })
+                    .map(move |(span, _)| (*span, name.clone()))
+            })
+            .collect(),
+    };
+
+    module.visit_mut_with(&mut renamer);
+
+    names.extend(renamed);
+
+    Ok(names)
+}
@@ -0,0 +1,137 @@
+use itertools::Itertools;
+use omm_core::TokenAllocator;
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_ecma_ast::{Callee, Class, ClassMember, Expr, Key, Module, PrivateName};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+/// How many times each private name appears in a class, the nested classes included.
+#[derive(Default)]
+struct PrivateNameCounter {
+    counts: FxHashMap<String, usize>,
+}
+
+impl Visit for PrivateNameCounter {
+    fn visit_private_name(&mut self, node: &PrivateName) {
+        *self.counts.entry(node.name.to_string()).or_default() += 1;
+    }
+}
+
+/// `#foo` of `#foo = 1`, `#foo() {}`, `get #foo() {}` or `accessor #foo`
+fn declared_name(member: &ClassMember) -> Option<&PrivateName> {
+    match member {
+        ClassMember::PrivateProp(prop) => Some(&prop.key),
+        ClassMember::PrivateMethod(method) => Some(&method.key),
+        ClassMember::AutoAccessor(accessor) => match &accessor.key {
+            Key::Private(key) => Some(key),
+            Key::Public(_) => None,
+        },
+        _ => None,
+    }
+}
+
+///
+/// Renames the private names of each class, the most used one takes the shortest name.
+///
+/// A private name refers to the closest class declaring it, a nested class never takes
+/// the names of the classes around it, it may refer to them.
+///
+#[derive(Default)]
+struct PrivateNameMangler {
+    // old => new, of the classes around
+    scopes: Vec<FxHashMap<String, String>>,
+}
+
+impl VisitMut for PrivateNameMangler {
+    fn visit_mut_class(&mut self, node: &mut Class) {
+        // `extends` and the decorators of the class are evaluated outside of it
+        node.decorators.visit_mut_with(self);
+        node.super_class.visit_mut_with(self);
+
+        let mut counter = PrivateNameCounter::default();
+
+        node.body.visit_with(&mut counter);
+
+        let declared = node
+            .body
+            .iter()
+            .filter_map(declared_name)
+            .map(|key| key.name.to_string())
+            .unique()
+            .collect::<Vec<_>>();
+
+        let mut allocator = TokenAllocator::new();
+
+        allocator.extends(
+            self.scopes
+                .iter()
+                .flat_map(|scope| scope.values().cloned())
+                .collect::<FxHashSet<_>>(),
+        );
+
+        let scope = declared
+            .into_iter()
+            .enumerate()
+            // the declaration order on ties
+            .sorted_by_key(|(index, name)| (usize::MAX - counter.counts[name], *index))
+            // nothing is reserved but the reserved words, the names never run out
+            .map(|(_, name)| (name, allocator.alloc().unwrap_or_else(|| unreachable!())))
+            .collect();
+
+        self.scopes.push(scope);
+        node.body.visit_mut_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_mut_private_name(&mut self, node: &mut PrivateName) {
+        if let Some(name) = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(node.name.as_str()))
+        {
+            node.name = name.as_str().into();
+        }
+    }
+}
+
+/// whether `eval(...)` is called, it can refer to the private names around it
+#[derive(Default)]
+struct DirectEvalFinder {
+    found: bool,
+}
+
+impl Visit for DirectEvalFinder {
+    fn visit_callee(&mut self, node: &Callee) {
+        if let Callee::Expr(box Expr::Ident(ident)) = node
+            && ident.sym == "eval"
+        {
+            self.found = true;
+        }
+
+        node.visit_children_with(self);
+    }
+}
+
+///
+/// Renames the private fields, methods and accessors of the classes, they are only seen
+/// inside the class body.
+///
+/// ```unknown
+/// class A { #veryLongCacheField; has(o) { return #veryLongCacheField in o } }
+///
+/// class A { #a; has(o) { return #a in o } }
+/// ```
+///
+/// Nothing is renamed when the module calls `eval`.
+///
+pub fn mangle_private_names(module: &mut Module) {
+    let mut finder = DirectEvalFinder::default();
+
+    module.visit_with(&mut finder);
+
+    if finder.found {
+        return;
+    }
+
+    module.visit_mut_with(&mut PrivateNameMangler::default());
+}
@@ -1,48 +1,69 @@
+use omm_core::{ident_len, is_ident_name, quoted_len, OccurrenceKind};
 use rustc_hash::{FxHashMap, FxHashSet};
 use swc_common::{Span, Spanned};
 use swc_ecma_ast::{
-    ComputedPropName, Expr, Ident, KeyValueProp, Lit, MemberExpr, MemberProp, Prop, PropName,
-    PropOrSpread,
+    AssignPat, BinExpr, BinaryOp, ComputedPropName, Constructor, Expr, Ident, IdentName,
+    JSXAttrValue, JSXElementName, JSXExpr, JSXExprContainer, KeyValuePatProp, KeyValueProp, Lit,
+    MemberExpr, MemberProp, ObjectPatProp, Pat, Prop, PropName, PropOrSpread, Str, SuperProp,
+    SuperPropExpr, TaggedTpl, Tpl, TplElement,
 };
 use swc_ecma_visit::{VisitMut, VisitMutWith};
 
-use omm_core::TokenAllocator;
+use crate::util::{constant_key, tpl_as_str};
 
-use crate::transformer::TransformContext;
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IdentReplacerConfig {
     pub skip_lits: FxHashSet<Span>,
     pub skip_ranges: FxHashSet<Span>,
+    /// the hoisted constants, see [`crate::util::constant_key`]
+    pub constants: FxHashMap<String, Lit>,
 }
 
 #[derive(Debug)]
 pub struct IdentReplacer {
     pub should_replace_ident_list: FxHashMap<String, FxHashSet<Span>>,
+    /// the names that have been used, only these are hoisted
     pub ident_map: FxHashMap<String, String>,
-    pub allocator: TokenAllocator,
+    /// whether a string is split into `a+"bar"`, see [`omm_core::find_affixes`]
+    pub composed: bool,
+    /// the strings split by an affix, span => (affix, kind)
+    affixes: FxHashMap<Span, (String, OccurrenceKind)>,
+    /// the names assigned before replacing, see [`omm_core::filter_cannot_compress_ident`]
+    names: FxHashMap<String, String>,
     config: IdentReplacerConfig,
 }
 
 impl IdentReplacer {
-    pub fn new(set: FxHashMap<String, FxHashSet<Span>>, config: IdentReplacerConfig) -> Self {
+    pub fn new(
+        set: FxHashMap<String, FxHashMap<Span, OccurrenceKind>>,
+        config: IdentReplacerConfig,
+        names: FxHashMap<String, String>,
+    ) -> Self {
+        let affixes = set
+            .iter()
+            .flat_map(|(value, spans)| {
+                spans
+                    .iter()
+                    .filter(|(_, kind)| {
+                        matches!(kind, OccurrenceKind::Prefix | OccurrenceKind::Suffix)
+                    })
+                    .map(|(span, kind)| (*span, (value.clone(), *kind)))
+            })
+            .collect();
+
         Self {
-            should_replace_ident_list: set,
-            allocator: TokenAllocator::new(),
+            should_replace_ident_list: set
+                .into_iter()
+                .map(|(value, spans)| (value, spans.into_keys().collect()))
+                .collect(),
+            names,
             ident_map: FxHashMap::default(),
+            composed: false,
+            affixes,
             config,
         }
     }
 
-    pub fn with_context(mut self, context: &TransformContext) -> Self {
-        self.extend_used_ident(context.options.preserve_keywords.iter().cloned().collect());
-        self
-    }
-
-    pub fn extend_used_ident(&mut self, set: FxHashSet<String>) {
-        self.allocator.extends(set);
-    }
-
     pub fn contain(&self, ident: &str, span: Span) -> bool {
         if self.config.skip_lits.contains(&span) {
             return false;
@@ -58,12 +79,20 @@ impl IdentReplacer {
             return v.to_string();
         }
 
-        let s = self.allocator.alloc();
+        let s = self.names[ident].clone();
 
         self.ident_map.insert(ident.to_string(), s.clone());
 
         s
     }
+
+    /// the initializer of the variable hoisting `value`
+    pub fn init_expr(&self, value: &str) -> Expr {
+        match self.config.constants.get(value) {
+            Some(lit) => Expr::Lit(lit.clone()),
+            None => Expr::Lit(Lit::Str(value.into())),
+        }
+    }
 }
 
 impl IdentReplacer {
@@ -85,6 +114,41 @@ impl IdentReplacer {
         }
     }
 
+    ///
+    /// `"foo/bar"` => `a+"bar"` and `"bar/foo"` => `"bar/"+a`, when `value` is split by
+    /// an affix.
+    ///
+    fn compose(&mut self, value: &str, span: Span) -> Option<Expr> {
+        let (affix, kind) = self.affixes.get(&span)?.clone();
+
+        if !self.contain(&affix, span) {
+            return None;
+        }
+
+        let ident = Box::new(Expr::Ident(self.create_ident(&affix)));
+        let str = |value: &str| {
+            Box::new(Expr::Lit(Lit::Str(Str {
+                span: Default::default(),
+                value: value.into(),
+                raw: None,
+            })))
+        };
+
+        let (left, right) = match kind {
+            OccurrenceKind::Prefix => (ident, str(value.strip_prefix(affix.as_str())?)),
+            _ => (str(value.strip_suffix(affix.as_str())?), ident),
+        };
+
+        self.composed = true;
+
+        Some(Expr::Bin(BinExpr {
+            span,
+            op: BinaryOp::Add,
+            left,
+            right,
+        }))
+    }
+
     fn replace_computed(&mut self, computed_props_name: &mut ComputedPropName) -> bool {
         if let Expr::Lit(Lit::Str(lit)) = &*computed_props_name.expr {
             let v = lit.value.as_str();
@@ -142,6 +206,28 @@ impl VisitMut for IdentReplacer {
         }
     }
 
+    fn visit_mut_super_prop_expr(&mut self, node: &mut SuperPropExpr) {
+        match &mut node.prop {
+            SuperProp::Ident(ident) => {
+                let v = ident.sym.as_str();
+                if self.contain(v, ident.span) {
+                    node.prop = SuperProp::Computed(self.create_computed_prop_name(v));
+                }
+            }
+            SuperProp::Computed(computed_prop_name) => {
+                if !self.replace_computed(computed_prop_name) {
+                    computed_prop_name.visit_mut_with(self);
+                }
+            }
+        }
+    }
+
+    fn visit_mut_constructor(&mut self, node: &mut Constructor) {
+        // the key is always `constructor`
+        node.params.visit_mut_with(self);
+        node.body.visit_mut_with(self);
+    }
+
     fn visit_mut_prop_or_spread(&mut self, node: &mut PropOrSpread) {
         match node {
             PropOrSpread::Prop(box prop) => match prop {
@@ -180,16 +266,104 @@ impl VisitMut for IdentReplacer {
     }
 
     fn visit_mut_expr(&mut self, node: &mut Expr) {
-        if let Expr::Lit(Lit::Str(lit)) = node {
-            let v = lit.value.as_str();
-            if self.contain(v, lit.span) {
-                *node = Expr::Ident(self.create_ident(v));
+        let value = match node {
+            Expr::Lit(Lit::Str(lit)) => Some((lit.value.to_string(), lit.span)),
+            // `foo` is replaced like "foo"
+            Expr::Tpl(tpl) => tpl_as_str(tpl).map(|value| (value.to_string(), tpl.span)),
+            Expr::Lit(lit) => constant_key(lit).map(|key| (key, lit.span())),
+            _ => None,
+        };
+
+        if let Some((v, span)) = value {
+            if self.contain(&v, span) {
+                *node = Expr::Ident(self.create_ident(&v));
+                return;
+            }
+
+            if let Some(expr) = self.compose(&v, span) {
+                *node = expr;
+                return;
             }
         }
 
         node.visit_mut_children_with(self);
     }
 
+    fn visit_mut_tpl(&mut self, node: &mut Tpl) {
+        node.exprs.visit_mut_with(self);
+
+        let mut quasis = Vec::with_capacity(node.quasis.len());
+        let mut exprs = Vec::with_capacity(node.exprs.len());
+        let mut prev_exprs = node.exprs.drain(..);
+
+        // `foo${x}` => `${a}${x}`
+        for quasi in node.quasis.drain(..) {
+            match quasi.cooked.as_deref() {
+                Some(v) if self.contain(v, quasi.span) => {
+                    let ident = self.create_ident(v);
+                    let empty = || TplElement {
+                        span: Default::default(),
+                        tail: false,
+                        cooked: Some("".into()),
+                        raw: "".into(),
+                    };
+
+                    quasis.push(empty());
+                    exprs.push(Box::new(Expr::Ident(ident)));
+                    quasis.push(TplElement {
+                        tail: quasi.tail,
+                        ..empty()
+                    });
+                }
+                _ => quasis.push(quasi),
+            }
+
+            exprs.extend(prev_exprs.next());
+        }
+
+        drop(prev_exprs);
+
+        node.quasis = quasis;
+        node.exprs = exprs;
+    }
+
+    fn visit_mut_jsx_attr_value(&mut self, node: &mut JSXAttrValue) {
+        // title="foo" => title={a}
+        if let JSXAttrValue::Lit(Lit::Str(lit)) = node {
+            let v = lit.value.to_string();
+
+            if self.contain(&v, lit.span) {
+                *node = JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                    span: Default::default(),
+                    expr: JSXExpr::Expr(Box::new(Expr::Ident(self.create_ident(&v)))),
+                });
+                return;
+            }
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_jsx_element_name(&mut self, node: &mut JSXElementName) {
+        // <div> => <A>, only given a name that is not an intrinsic element
+        if let JSXElementName::Ident(ident) = node {
+            let v = ident.sym.to_string();
+
+            if self.contain(&v, ident.span) {
+                *ident = self.create_ident(&v);
+                return;
+            }
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_tagged_tpl(&mut self, node: &mut TaggedTpl) {
+        // the tag sees the raw strings, so they are left as they are
+        node.tag.visit_mut_with(self);
+        node.tpl.exprs.visit_mut_with(self);
+    }
+
     fn visit_mut_prop_name(&mut self, node: &mut PropName) {
         let mut is_replaced = false;
         match node {
@@ -240,4 +414,102 @@ impl VisitMut for IdentReplacer {
             node.visit_mut_children_with(self);
         }
     }
+
+    fn visit_mut_object_pat_prop(&mut self, node: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(prop) = node {
+            let name = prop.key.id.sym.to_string();
+
+            if self.contain(&name, prop.key.id.span) {
+                let binding = Box::new(Pat::Ident(prop.key.clone()));
+
+                // `{ foo = 1 }` => `{ [a]: foo = 1 }`
+                let value = match prop.value.take() {
+                    Some(right) => Box::new(Pat::Assign(AssignPat {
+                        span: prop.span,
+                        left: binding,
+                        right,
+                    })),
+                    None => binding,
+                };
+
+                *node = ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Computed(self.create_computed_prop_name(&name)),
+                    value,
+                });
+            }
+        }
+
+        node.visit_mut_children_with(self);
+    }
+}
+
+///
+/// Writes the string keys that are identifiers as identifiers, before the strings are
+/// collected, so the cost of hoisting them is measured against the shorter form.
+///
+/// ```unknown
+/// obj["someKey"]      => obj.someKey
+/// { "someKey": 1 }    => { someKey: 1 }
+/// { ["someKey"]: 1 }  => { someKey: 1 }
+/// ```
+///
+pub struct KeyNormalizer {
+    pub ascii_only: bool,
+}
+
+impl KeyNormalizer {
+    /// `someKey` of `"someKey"`, when it is not longer than the string
+    fn ident_name(&self, str: &Str) -> Option<IdentName> {
+        let value = str.value.as_str();
+
+        (is_ident_name(value)
+            && ident_len(value, self.ascii_only) <= quoted_len(value, self.ascii_only))
+        .then(|| IdentName::new(str.value.clone(), str.span))
+    }
+
+    fn computed_ident_name(&self, computed: &ComputedPropName) -> Option<IdentName> {
+        match &*computed.expr {
+            Expr::Lit(Lit::Str(str)) => self.ident_name(str),
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for KeyNormalizer {
+    fn visit_mut_member_expr(&mut self, node: &mut MemberExpr) {
+        if let MemberProp::Computed(computed) = &node.prop
+            && let Some(ident) = self.computed_ident_name(computed)
+        {
+            node.prop = MemberProp::Ident(ident);
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_super_prop_expr(&mut self, node: &mut SuperPropExpr) {
+        if let SuperProp::Computed(computed) = &node.prop
+            && let Some(ident) = self.computed_ident_name(computed)
+        {
+            node.prop = SuperProp::Ident(ident);
+        }
+
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop_name(&mut self, node: &mut PropName) {
+        let ident = match node {
+            PropName::Str(str) => self.ident_name(str),
+            // `{ ["__proto__"]: 1 }` does not set the prototype, `["constructor"]() {}` is a
+            // method and `static ["prototype"]` is allowed
+            PropName::Computed(computed) => self
+                .computed_ident_name(computed)
+                .filter(|ident| !matches!(&*ident.sym, "__proto__" | "constructor" | "prototype")),
+            _ => None,
+        };
+
+        match ident {
+            Some(ident) => *node = PropName::Ident(ident),
+            None => node.visit_mut_children_with(self),
+        }
+    }
 }
@@ -0,0 +1,247 @@
+use itertools::Itertools;
+use omm_core::{is_ident_name, select_shapes, ObjectShape, TokenAllocator};
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_common::{Span, DUMMY_SP};
+use swc_ecma_ast::{
+    ArrowExpr, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Expr, ExprOrSpread, Ident,
+    IdentName, KeyValueProp, Module, ObjectLit, ParenExpr, Pat, Prop, PropName, PropOrSpread,
+    UnaryOp, VarDeclarator,
+};
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use crate::{transformer::TransformContext, util::declare_hoisted};
+
+///
+/// The keys and the values of an object literal, only given plain keys and values that
+/// can be evaluated in any order, see [`is_pure`].
+///
+fn shape_of(node: &ObjectLit) -> Option<(Vec<String>, Vec<Expr>)> {
+    let mut keys = Vec::with_capacity(node.props.len());
+    let mut values = Vec::with_capacity(node.props.len());
+
+    for prop in &node.props {
+        let (key, value) = match prop {
+            PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp { key, value })) => {
+                let key = match key {
+                    PropName::Ident(ident) => ident.sym.to_string(),
+                    PropName::Str(str) => str.value.to_string(),
+                    _ => return None,
+                };
+
+                (key, *value.clone())
+            }
+            // { foo } => f(foo)
+            PropOrSpread::Prop(box Prop::Shorthand(ident)) => {
+                (ident.sym.to_string(), Expr::Ident(ident.clone()))
+            }
+            _ => return None,
+        };
+
+        // `__proto__` sets the prototype
+        if key == "__proto__" || keys.contains(&key) || !is_pure(&value) {
+            return None;
+        }
+
+        keys.push(key);
+        values.push(value);
+    }
+
+    if keys.is_empty() {
+        return None;
+    }
+
+    Some((keys, values))
+}
+
+/// whether evaluating `expr` has no side effect
+fn is_pure(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Ident(_) => true,
+        // an anonymous function takes its name from the key, `{ foo: function () {} }`
+        Expr::Fn(function) => function.ident.is_some(),
+        Expr::Tpl(tpl) => tpl.exprs.iter().all(|expr| is_pure(expr)),
+        Expr::Paren(paren) => is_pure(&paren.expr),
+        // -1, !0, void 0
+        Expr::Unary(unary) => match unary.op {
+            UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Tilde => {
+                matches!(&*unary.arg, Expr::Lit(_))
+            }
+            UnaryOp::Bang | UnaryOp::Void | UnaryOp::TypeOf => is_pure(&unary.arg),
+            UnaryOp::Delete => false,
+        },
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .flatten()
+            .all(|elem| elem.spread.is_none() && is_pure(&elem.expr)),
+        Expr::Object(object) => object.props.is_empty() || shape_of(object).is_some(),
+        _ => false,
+    }
+}
+
+#[derive(Default)]
+struct ShapeCollector {
+    shapes: FxHashMap<ObjectShape, Vec<Span>>,
+    /// every name of the module, the factories can not take them
+    idents: FxHashSet<String>,
+}
+
+impl Visit for ShapeCollector {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.idents.insert(ident.sym.to_string());
+    }
+
+    fn visit_object_lit(&mut self, node: &ObjectLit) {
+        if let Some((keys, _)) = shape_of(node) {
+            self.shapes
+                .entry(ObjectShape::new(keys))
+                .or_default()
+                .push(node.span);
+        }
+
+        node.visit_children_with(self);
+    }
+}
+
+/// Replaces the object literals of the selected shapes, keyed by their span.
+struct ShapeReplacer {
+    sites: FxHashMap<Span, String>,
+}
+
+impl VisitMut for ShapeReplacer {
+    fn visit_mut_expr(&mut self, node: &mut Expr) {
+        if let Expr::Object(object) = node
+            && let Some(name) = self.sites.get(&object.span)
+            && let Some((_, values)) = shape_of(object)
+        {
+            // {id:1,name:"x"} => f(1,"x")
+            *node = Expr::Call(CallExpr {
+                span: object.span,
+                ctxt: Default::default(),
+                callee: Callee::Expr(Box::new(Expr::Ident(name.as_str().into()))),
+                args: values
+                    .into_iter()
+                    .map(|expr| ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(expr),
+                    })
+                    .collect(),
+                type_args: None,
+            });
+        }
+
+        node.visit_mut_children_with(self);
+    }
+}
+
+// (a,b,c)=>({id:a,name:b,type:c})
+fn factory_expr(shape: &ObjectShape) -> Expr {
+    let params = shape.params();
+
+    let props = shape
+        .keys
+        .iter()
+        .zip(&params)
+        .map(|(key, param)| {
+            let key = if is_ident_name(key) {
+                PropName::Ident(IdentName::new(key.as_str().into(), DUMMY_SP))
+            } else {
+                PropName::Str(key.as_str().into())
+            };
+
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key,
+                value: Box::new(Expr::Ident(param.as_str().into())),
+            })))
+        })
+        .collect();
+
+    Expr::Arrow(ArrowExpr {
+        params: params
+            .iter()
+            .map(|param| {
+                Pat::Ident(BindingIdent {
+                    id: param.as_str().into(),
+                    type_ann: None,
+                })
+            })
+            .collect(),
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props,
+            })),
+        })))),
+        ..Default::default()
+    })
+}
+
+///
+/// Creates the object literals sharing their keys with a factory declared at the top of
+/// the module, when it is smaller.
+///
+/// ```unknown
+/// [{ id: 1, name: "x" }, { id: 2, name: "y" }] => [a(1, "x"), a(2, "y")]
+///
+/// var a = (a, b) => ({ id: a, name: b });
+/// ```
+///
+/// Only the literals whose values have no side effect are created by a factory, the keys
+/// are in the same order.
+///
+pub fn factor_object_shapes(module: &mut Module, context: &TransformContext) {
+    let mut collector = ShapeCollector::default();
+
+    module.visit_with(&mut collector);
+
+    let candidates = collector
+        .shapes
+        .iter()
+        .map(|(shape, sites)| (shape.clone(), sites.len()))
+        .collect::<Vec<_>>();
+
+    let mut allocator = TokenAllocator::new().with_reserved(context.reserved.clone());
+
+    allocator.extends(collector.idents.clone());
+
+    let selected = select_shapes(
+        candidates,
+        &allocator,
+        context.options.ascii_only,
+        &context.options.cost_strategy,
+    );
+
+    if selected.is_empty() {
+        return;
+    }
+
+    let mut replacer = ShapeReplacer {
+        sites: selected
+            .iter()
+            .flat_map(|(shape, name)| {
+                collector.shapes[shape]
+                    .iter()
+                    .map(move |span| (*span, name.clone()))
+            })
+            .collect(),
+    };
+
+    module.visit_mut_with(&mut replacer);
+
+    let decls = selected
+        .iter()
+        .sorted_by_key(|(_, name)| name.to_string())
+        .map(|(shape, name)| VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: name.as_str().into(),
+                type_ann: None,
+            }),
+            init: Some(Box::new(factory_expr(shape))),
+            definite: false,
+        })
+        .collect();
+
+    declare_hoisted(module, decls);
+}
@@ -1,27 +1,38 @@
-use std::{cell::OnceCell, path::Path, sync::Arc};
+use std::{cell::OnceCell, collections::BTreeMap, path::Path, sync::Arc};
 
 use itertools::Itertools;
-use omm_core::filter_cannot_compress_ident;
-use rustc_hash::FxHashMap;
-use serde::{Deserialize, Serialize};
-use swc_common::{FileName, Globals};
-use swc_ecma_ast::{
-    BindingIdent, Decl, Expr, Lit, Module, ModuleItem, Pat, Stmt, VarDecl, VarDeclKind,
-    VarDeclarator,
+use omm_core::{
+    filter_by_compressed_size, filter_cannot_compress_ident, find_affixes, fit_size_budget,
+    should_replace_occurrence, Affix, CompressObjective, CostStrategy, OccurrenceKind, Occurrences,
+    ReservedNames, ReservedOption, TokenAllocator,
 };
+use regex::Regex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use swc_common::{FileName, Globals, SourceMap};
+use swc_ecma_ast::{BindingIdent, Lit, Module, Pat, VarDeclarator};
 use swc_ecma_parser::{EsSyntax, Syntax, TsSyntax};
+use swc_ecma_transforms::fixer;
 use swc_ecma_visit::{VisitMutWith, VisitWith};
 
 use crate::{
+    alias::alias_global_chains,
+    local_alias::alias_local_chains,
+    mangle::mangle_properties,
+    private::mangle_private_names,
     replacer::IdentReplacerConfig,
+    shape::factor_object_shapes,
     util::{
-        resolve_module_mark,
+        declare_hoisted, resolve_module_mark,
         script::{codegen, parse, try_build_output_sourcemap},
         try_with,
     },
 };
 
-use super::{collector::IdentCollector, replacer::IdentReplacer};
+use super::{
+    collector::{IdentCollector, IdentCollectorData},
+    replacer::{IdentReplacer, KeyNormalizer},
+};
 
 type Result<T> = anyhow::Result<T>;
 
@@ -31,23 +42,38 @@ pub enum ModuleType {
     #[default]
     Typescript,
     Javascript,
+    Jsx,
+    Tsx,
+}
+
+impl ModuleType {
+    pub fn is_typescript(&self) -> bool {
+        matches!(self, ModuleType::Typescript | ModuleType::Tsx)
+    }
 }
 
 pub fn module_type_from_option(options: &TransformOption) -> ModuleType {
-    match options.module_type {
-        Some(ModuleType::Typescript) => ModuleType::Typescript,
-        Some(ModuleType::Javascript) => ModuleType::Javascript,
+    match &options.module_type {
+        Some(module_type) => module_type.clone(),
         None => options
             .filename
             .as_ref()
             .map_or(ModuleType::Typescript, |filename| {
-                if [".ts", ".tsx", ".mts", ".cts"]
+                if filename.ends_with(".tsx") {
+                    return ModuleType::Tsx;
+                }
+
+                if [".ts", ".mts", ".cts"]
                     .iter()
                     .any(|ext| filename.ends_with(ext))
                 {
                     return ModuleType::Typescript;
                 }
 
+                if filename.ends_with(".jsx") {
+                    return ModuleType::Jsx;
+                }
+
                 ModuleType::Javascript
             }),
     }
@@ -55,8 +81,16 @@ pub fn module_type_from_option(options: &TransformOption) -> ModuleType {
 
 pub fn syntax_from_option(module_type: &ModuleType) -> Syntax {
     match module_type {
-        ModuleType::Typescript => Syntax::Es(EsSyntax::default()),
-        ModuleType::Javascript => Syntax::Typescript(TsSyntax::default()),
+        ModuleType::Typescript => Syntax::Typescript(TsSyntax::default()),
+        ModuleType::Tsx => Syntax::Typescript(TsSyntax {
+            tsx: true,
+            ..Default::default()
+        }),
+        ModuleType::Javascript => Syntax::Es(EsSyntax::default()),
+        ModuleType::Jsx => Syntax::Es(EsSyntax {
+            jsx: true,
+            ..Default::default()
+        }),
     }
 }
 
@@ -64,45 +98,210 @@ pub fn hosting_variable(module: &mut Module, replacer: IdentReplacer) {
     let mut decls: Vec<VarDeclarator> = vec![];
     for (val, key) in replacer
         .ident_map
-        .into_iter()
+        .iter()
         .sorted_by_key(|(_, ident)| ident.to_string())
     {
         decls.push(VarDeclarator {
             span: Default::default(),
             name: Pat::Ident(BindingIdent {
-                id: key.into(),
+                id: key.as_str().into(),
                 type_ann: None,
             }),
-            init: Some(Box::new(Expr::Lit(Lit::Str(val.into())))),
+            init: Some(Box::new(replacer.init_expr(val))),
             definite: false,
         });
     }
 
-    if decls.is_empty() {
-        return;
+    declare_hoisted(module, decls);
+}
+
+fn create_allocator(
+    used_ident: FxHashSet<String>,
+    module: &Module,
+    context: &TransformContext,
+) -> TokenAllocator {
+    let mut allocator = TokenAllocator::new().with_reserved(context.reserved.clone());
+
+    if context.options.char_frequency {
+        // the printed module, before anything is hoisted
+        let code = codegen(
+            &mut module.clone(),
+            context.cm.clone(),
+            None,
+            context.options.ascii_only,
+        )
+        .unwrap_or_default();
+
+        allocator = allocator.with_char_frequency(&code);
     }
 
-    module.body.insert(
-        0,
-        ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
-            span: Default::default(),
-            ctxt: Default::default(),
-            kind: VarDeclKind::Var,
-            declare: false,
-            decls,
-        })))),
+    allocator.extends(used_ident);
+
+    match &context.options.name_cache {
+        Some(cache) => allocator.with_cache(cache.clone()),
+        None => allocator,
+    }
+}
+
+fn replace_ident(
+    module: &mut Module,
+    field: &IdentCollectorData,
+    ranked: &[(String, Occurrences)],
+    config: &IdentReplacerConfig,
+    allocator: &TokenAllocator,
+    ascii_only: bool,
+) -> FxHashMap<String, String> {
+    // the names follow the rank, not the order the strings appear in the module
+    let mut allocator = allocator.clone();
+    let names = ranked
+        .iter()
+        .map_while(|(ident, _)| Some((ident.clone(), allocator.alloc_for(ident)?)))
+        .collect::<FxHashMap<_, _>>();
+
+    // replace ident
+    let mut replacer = IdentReplacer::new(
+        field
+            .iter()
+            .filter_map(|(k, (spans, _))| {
+                let name = names.get(k)?;
+
+                // the usages that would grow, e.g. `{ foo }`, are left as they are
+                let spans = spans
+                    .iter()
+                    .filter(|(_, kind)| should_replace_occurrence(name, k, **kind, ascii_only))
+                    .map(|(span, kind)| (*span, *kind))
+                    .collect();
+
+                Some((k.clone(), spans))
+            })
+            .collect(),
+        config.clone(),
+        names,
     );
+
+    module.visit_mut_with(&mut replacer);
+
+    // `a+"bar"` needs parentheses in `(a+"bar").length`
+    if replacer.composed {
+        module.visit_mut_with(&mut fixer(None));
+    }
+
+    let hoisted = replacer.ident_map.clone();
+
+    // insert replaced ident
+    hosting_variable(module, replacer);
+
+    hoisted
 }
 
-pub fn object_member_minify(module: &mut Module, context: &TransformContext) {
+///
+/// Adds the prefixes and suffixes shared by the strings, each usage of a split string is
+/// recorded under its affix, see [`find_affixes`].
+///
+fn add_affixes(field: &mut IdentCollectorData, ascii_only: bool, strategy: &CostStrategy) {
+    let occurrences = field
+        .iter()
+        .map(|(value, (_, occurrences))| (value.clone(), occurrences.clone()))
+        .collect();
+
+    for Affix {
+        value,
+        kind,
+        strings,
+    } in find_affixes(&occurrences, ascii_only, strategy)
+    {
+        let spans = strings
+            .iter()
+            .flat_map(|string| &field[string].0)
+            .filter(|(_, kind)| **kind == OccurrenceKind::Lit)
+            .map(|(span, _)| *span)
+            .collect::<Vec<_>>();
+
+        let (affix_spans, affix_occurrences) = field.entry(value).or_default();
+
+        for span in spans {
+            affix_spans.insert(span, kind);
+            affix_occurrences.add(kind);
+        }
+    }
+}
+
+///
+/// A constant written like a string of the module, e.g. `255` and `"255"`, is left as it is,
+/// both would take the same variable.
+///
+fn drop_conflicting_constants(
+    field: &mut IdentCollectorData,
+    constants: &mut FxHashMap<String, Lit>,
+) {
+    constants.retain(|key, _| {
+        let Some((spans, occurrences)) = field.get_mut(key) else {
+            return false;
+        };
+
+        if occurrences.total() == occurrences.count(OccurrenceKind::Constant) {
+            return true;
+        }
+
+        spans.retain(|_, kind| *kind != OccurrenceKind::Constant);
+        *occurrences = occurrences
+            .iter()
+            .filter(|(kind, _)| *kind != OccurrenceKind::Constant)
+            .fold(Occurrences::new(), |occurrences, (kind, count)| {
+                occurrences.with(kind, count)
+            });
+
+        false
+    });
+}
+
+///
+/// Hoists the strings worth it, returns the hoisted strings and their names.
+///
+pub fn object_member_minify(
+    module: &mut Module,
+    context: &TransformContext,
+) -> FxHashMap<String, String> {
+    module.visit_mut_with(&mut KeyNormalizer {
+        ascii_only: context.options.ascii_only,
+    });
+
+    if context.options.alias_local_chains {
+        resolve_module_mark(
+            module,
+            context.module_type.is_typescript(),
+            &context.globals,
+        );
+
+        alias_local_chains(module, context);
+    }
+
+    if context.options.mangle_private_names {
+        mangle_private_names(module);
+    }
+
+    if !context.options.global_aliases.is_empty() {
+        let (unresolved_mark, _) = resolve_module_mark(
+            module,
+            context.module_type.is_typescript(),
+            &context.globals,
+        );
+
+        alias_global_chains(module, context, unresolved_mark);
+    }
+
+    if context.options.factor_object_shapes {
+        factor_object_shapes(module, context);
+    }
+
     let (unresolved_mark, top_level_mark) = resolve_module_mark(
         module,
-        matches!(context.module_type, ModuleType::Typescript),
+        context.module_type.is_typescript(),
         &context.globals,
     );
 
     // collection
-    let mut collector = IdentCollector::new(unresolved_mark, top_level_mark).with_context(context);
+    let mut collector = IdentCollector::new(top_level_mark, unresolved_mark).with_context(context);
 
     module.visit_with(&mut collector);
 
@@ -111,48 +310,79 @@ pub fn object_member_minify(module: &mut Module, context: &TransformContext) {
         used_ident,
         skip_lits,
         skip_ranges,
+        mut constants,
         ..
     } = collector;
 
+    drop_conflicting_constants(&mut field, &mut constants);
+
+    let ascii_only = context.options.ascii_only;
+    let strategy = &context.options.cost_strategy;
+
+    if context.options.compose_strings {
+        add_affixes(&mut field, ascii_only, strategy);
+    }
+
     let filterable_map = field
         .iter()
-        .map(|(ident, (_, count))| (ident.clone(), *count))
+        .map(|(ident, (_, occurrences))| (ident.clone(), occurrences.clone()))
         .collect::<FxHashMap<_, _>>();
 
-    // filter does not have to be replaced
-    let map = filter_cannot_compress_ident(filterable_map);
+    let config = IdentReplacerConfig {
+        skip_lits,
+        skip_ranges,
+        constants,
+    };
 
-    if map.is_empty() {
-        return;
-    }
+    let allocator = create_allocator(used_ident, module, context);
 
-    let keys = field.keys().cloned().collect::<Vec<_>>();
-    for key in keys {
-        if !map.contains_key(&key) {
-            field.remove(&key);
+    // filter does not have to be replaced
+    let ranked = match context.options.objective {
+        CompressObjective::Raw => {
+            filter_cannot_compress_ident(filterable_map, &allocator, ascii_only, strategy)
         }
-    }
+        objective => {
+            let origin: &Module = module;
+
+            filter_by_compressed_size(
+                filterable_map,
+                &allocator,
+                ascii_only,
+                strategy,
+                objective,
+                |ranked| {
+                    let mut module = origin.clone();
+
+                    replace_ident(&mut module, &field, ranked, &config, &allocator, ascii_only);
+
+                    codegen(&mut module, context.cm.clone(), None, ascii_only).ok()
+                },
+            )
+        }
+    };
 
-    drop(map);
+    let ranked = match context.options.size_budget {
+        Some(budget) if !ranked.is_empty() => {
+            let origin: &Module = module;
 
-    // replace ident
-    let mut replacer = IdentReplacer::new(
-        field
-            .into_iter()
-            .map(|(k, (spans, _))| (k, spans))
-            .collect(),
-        IdentReplacerConfig {
-            skip_lits,
-            skip_ranges,
-        },
-    )
-    .with_context(context);
-
-    replacer.extend_used_ident(used_ident);
-    module.visit_mut_with(&mut replacer);
+            fit_size_budget(ranked, &allocator, ascii_only, strategy, budget, |ranked| {
+                let mut module = origin.clone();
 
-    // insert replaced ident
-    hosting_variable(module, replacer);
+                replace_ident(&mut module, &field, ranked, &config, &allocator, ascii_only);
+
+                // a render that fails never fits
+                codegen(&mut module, context.cm.clone(), None, ascii_only)
+                    .map_or(usize::MAX, |code| code.len())
+            })
+        }
+        _ => ranked,
+    };
+
+    if ranked.is_empty() {
+        return FxHashMap::default();
+    }
+
+    replace_ident(module, &field, &ranked, &config, &allocator, ascii_only)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -222,6 +452,37 @@ pub struct StringLitOptions {
     pub content: String,
 }
 
+///
+/// The properties renamed across the module, see [`TransformOption::mangle_properties`].
+///
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManglePropertiesOption {
+    ///
+    /// the regular expression selecting the properties to rename, e.g. `"^_"`
+    ///
+    pub regex: String,
+    ///
+    /// the properties never renamed, besides the paths of [`TransformOption::ignore_words`].
+    ///
+    /// default: `[]`
+    #[serde(default)]
+    pub reserved: Vec<String>,
+    ///
+    /// the names the properties took in previous builds or chunks, property => name.
+    ///
+    /// a property keeps its name, new properties never take a cached name. A property
+    /// left as it is is cached as itself, e.g. `"_foo": "_foo"`, so the other chunks leave it
+    /// too, and a renamed one is renamed in the strings naming it, e.g. `"_foo" in obj`, but
+    /// not in the other strings. The transform fails when a property can not keep its cached
+    /// name, e.g. a property left as it is in the chunk already has it. The updated cache is
+    /// returned in [`TransformResult::property_cache`].
+    ///
+    /// default: `None`
+    #[serde(default)]
+    pub name_cache: Option<BTreeMap<String, String>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type")]
 pub enum IgnoreWord {
@@ -270,6 +531,11 @@ impl<T: AsRef<str>> From<T> for IgnoreWord {
     }
 }
 
+/// an option left at its default is not serialized, e.g. in the snapshots of the fixtures
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TransformOption {
@@ -278,8 +544,190 @@ pub struct TransformOption {
     #[serde(default)]
     pub enable_source_map: bool,
     pub module_type: Option<ModuleType>,
+    ///
+    /// names a hoisted variable must not take, added to [`ReservedOption::names`] of
+    /// [`TransformOption::reserved`].
+    ///
     #[serde(default)]
     pub preserve_keywords: Vec<String>,
+    ///
+    /// the names a hoisted variable must not take, besides the javascript reserved words.
+    ///
+    /// the hoisted variables are declared at the top level, select the profiles of the
+    /// environments running the output so their globals, e.g. `name` or `self` in a browser,
+    /// are not overwritten. `patterns` are regular expressions.
+    ///
+    /// ```json
+    /// { "profiles": ["browser"], "names": ["myGlobal"], "patterns": ["^__"] }
+    /// ```
+    ///
+    /// default: `{}`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub reserved: ReservedOption,
+    ///
+    /// the size to reduce when deciding which strings to hoist.
+    ///
+    /// `gzip` and `brotli` print and compress the module several times, slower than `raw`,
+    /// dropping the strings that make it larger on a best-effort basis.
+    ///
+    /// default: `raw`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub objective: CompressObjective,
+    ///
+    /// escape non-ascii characters in the output, e.g. `"中"` => `"\u4E2D"`
+    ///
+    /// default: `false`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ascii_only: bool,
+    ///
+    /// the thresholds a string must pass to be hoisted, a preset and the values overriding it.
+    ///
+    /// ```json
+    /// { "preset": "conservative", "minLength": 4, "minOccurrences": 3, "minSavedBytes": 10 }
+    /// ```
+    ///
+    /// default: `{ "preset": "balanced" }`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub cost_strategy: CostStrategy,
+    ///
+    /// the bytes the output should fit in, hoisting stops once the estimated output fits.
+    ///
+    /// the candidates rewriting the fewest usages for each byte saved are hoisted first,
+    /// see [`TransformResult::size_budget`] for whether it fits.
+    ///
+    /// default: `None`, hoist everything worth it
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub size_budget: Option<usize>,
+    ///
+    /// return the output even when it is not smaller than the input.
+    ///
+    /// by default the input and its source map are returned untouched in that case,
+    /// see [`TransformResult::fallback`]. The input is always transformed when
+    /// [`TransformOption::ascii_only`] is set and it has non-ascii characters.
+    ///
+    /// default: `false`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub allow_larger: bool,
+    ///
+    /// order the characters of the hoisted names by how often they appear in the module,
+    /// so the names reuse the characters common in it and compress better.
+    ///
+    /// default: `false`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub char_frequency: bool,
+    ///
+    /// hoist the prefixes and suffixes shared by strings not worth hoisting on their own.
+    ///
+    /// ```js
+    /// "./components/Button" => a+"Button"
+    /// "./components/Input"  => a+"Input"
+    /// ```
+    ///
+    /// default: `false`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub compose_strings: bool,
+    ///
+    /// the names hoisted strings took in previous builds, string => name.
+    ///
+    /// a string keeps its name when it is hoisted again, new strings never take a cached name.
+    /// the updated cache is returned in [`TransformResult::name_cache`].
+    ///
+    /// ```json
+    /// { "foo": "a", "bar": "b" }
+    /// ```
+    ///
+    /// default: `None`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub name_cache: Option<BTreeMap<String, String>>,
+    ///
+    /// the member chains of globals aliased by a hoisted variable when repeated, the chains
+    /// assigned in the module or whose root is shadowed are left as they are.
+    ///
+    /// ```js
+    /// Object.defineProperty(o, "foo", {}) => a(o, "foo", {})
+    /// document.createElement("div")       => b("div")
+    ///
+    /// var a = Object.defineProperty, b = document.createElement.bind(document);
+    /// ```
+    ///
+    /// a called chain is bound to its object. The chains are read once when the module is
+    /// evaluated, only list the ones that exist by then and never change. A chain whose root
+    /// is not a global of the language, e.g. `document`, is only aliased when the module
+    /// already reads it when evaluated, outside any function, branch or `typeof`.
+    ///
+    /// default: `[]`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub global_aliases: Vec<String>,
+    ///
+    /// create the object literals sharing their keys with a hoisted factory, when smaller.
+    ///
+    /// ```js
+    /// [{ id: 1, name: "x" }, { id: 2, name: "y" }] => [a(1, "x"), a(2, "y")]
+    ///
+    /// var a = (a, b) => ({ id: a, name: b });
+    /// ```
+    ///
+    /// only the literals with plain keys and values without side effects are created by a
+    /// factory, e.g. literals, variables and arrays of them.
+    ///
+    /// default: `false`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub factor_object_shapes: bool,
+    ///
+    /// rename the private fields, methods and accessors of each class, they can not be seen
+    /// outside the class body.
+    ///
+    /// ```js
+    /// class A { #veryLongCacheField; has(o) { return #veryLongCacheField in o } }
+    ///
+    /// class A { #a; has(o) { return #a in o } }
+    /// ```
+    ///
+    /// nothing is renamed when the module calls `eval`.
+    ///
+    /// default: `false`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mangle_private_names: bool,
+    ///
+    /// rename the properties matching `regex` in member expressions, object literals, classes
+    /// and destructuring, only select the properties never read from outside the output.
+    ///
+    /// ```js
+    /// class A { _cache = {}; get(k) { return this._cache[k] } }
+    ///
+    /// class A { a = {}; get(k) { return this.a[k] } }
+    /// ```
+    ///
+    /// a property also written as a string, e.g. `"_foo" in obj`, or naming
+    /// a jsx attribute is left as it is, unless [`ManglePropertiesOption::name_cache`] renames
+    /// it. The output is returned even when it is not smaller, so every chunk sharing the
+    /// cache agrees on the names.
+    ///
+    /// ```json
+    /// { "regex": "^_", "reserved": ["_keep"], "nameCache": { "_cache": "a" } }
+    /// ```
+    ///
+    /// default: `None`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub mangle_properties: Option<ManglePropertiesOption>,
+    ///
+    /// alias the member chains read repeatedly in a function with a `const` declared in it,
+    /// when nothing between the reads may write them.
+    ///
+    /// ```js
+    /// function f() { use(this.props.store.state.user.name, this.props.store.state.user.id) }
+    ///
+    /// function f() { const a = this.props.store.state.user; use(a.name, a.id) }
+    /// ```
+    ///
+    /// reading a property is assumed to have no side effect, the prefixes of an aliased chain
+    /// are read once, so do not enable it when a getter on them does more than return a value.
+    /// A call, `await`, a write to any property, which may run a setter, a conversion like
+    /// `obj + ""`, which may run `valueOf`, or a loop running again ends the reads aliased.
+    ///
+    /// default: `false`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub alias_local_chains: bool,
 
     // TODO: support ignore object and object ident
     #[serde(default)]
@@ -290,6 +738,35 @@ impl TransformOption {
     fn filename(&self) -> String {
         self.filename.clone().unwrap_or("input.js".to_string())
     }
+
+    fn property_regex(&self) -> Result<Option<Regex>> {
+        Ok(self
+            .mangle_properties
+            .as_ref()
+            .map(|option| Regex::new(&option.regex))
+            .transpose()?)
+    }
+
+    fn reserved_names(&self) -> Result<ReservedNames> {
+        let mut reserved = self.reserved.clone();
+
+        reserved
+            .names
+            .extend(self.preserve_keywords.iter().cloned());
+
+        let reserved = reserved.compile()?;
+
+        // e.g. `.*`, a hoisted variable could never be named
+        if TokenAllocator::new()
+            .with_reserved(reserved.clone())
+            .alloc()
+            .is_none()
+        {
+            anyhow::bail!("the reserved patterns leave no name for a hoisted variable");
+        }
+
+        Ok(reserved)
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -297,24 +774,86 @@ impl TransformOption {
 pub struct TransformResult {
     pub content: String,
     pub map: Option<String>,
+    /// only present when [`TransformOption::size_budget`] is set
+    pub size_budget: Option<SizeBudgetResult>,
+    /// why the input is returned as it is, `None` when it is transformed
+    pub fallback: Option<Fallback>,
+    ///
+    /// [`TransformOption::name_cache`] with the names of the newly hoisted strings,
+    /// only present when it is set
+    ///
+    pub name_cache: Option<BTreeMap<String, String>>,
+    ///
+    /// [`ManglePropertiesOption::name_cache`] with the newly renamed and kept properties,
+    /// only present when [`TransformOption::mangle_properties`] is set
+    ///
+    pub property_cache: Option<BTreeMap<String, String>>,
+}
+
+///
+/// The output is not smaller than the input, so `content` and `map` are the input ones.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Fallback {
+    /// the printed output has at least as many bytes as the input
+    RawSizeNotReduced,
+    /// the printed output is smaller, but not once compressed with [`TransformOption::objective`]
+    CompressedSizeNotReduced,
+}
+
+impl Fallback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Fallback::RawSizeNotReduced => "rawSizeNotReduced",
+            Fallback::CompressedSizeNotReduced => "compressedSizeNotReduced",
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeBudgetResult {
+    pub budget: usize,
+    /// bytes of the output
+    pub size: usize,
+    pub met: bool,
+    /// `budget - size`, negative when the output does not fit
+    pub remaining: isize,
+}
+
+impl SizeBudgetResult {
+    fn new(budget: usize, size: usize) -> Self {
+        Self {
+            budget,
+            size,
+            met: size <= budget,
+            remaining: budget as isize - size as isize,
+        }
+    }
 }
 
 #[allow(dead_code)]
 pub struct TransformContext {
     pub module_type: ModuleType,
     pub options: TransformOption,
+    pub reserved: ReservedNames,
     pub globals: Arc<Globals>,
+    pub cm: Arc<SourceMap>,
 }
 
 #[allow(clippy::declare_interior_mutable_const)]
 const SWC_GLOBALS: OnceCell<Arc<Globals>> = OnceCell::new();
 
 pub fn transform(content: String, options: TransformOption) -> Result<TransformResult> {
+    let source_map = Arc::new(SourceMap::default());
     let context = TransformContext {
         module_type: module_type_from_option(&options),
+        reserved: options.reserved_names()?,
         options,
         #[allow(clippy::borrow_interior_mutable_const)]
         globals: SWC_GLOBALS.get_or_init(|| Arc::new(Globals::new())).clone(),
+        cm: source_map.clone(),
     };
     let filename = context.options.filename();
 
@@ -322,17 +861,28 @@ pub fn transform(content: String, options: TransformOption) -> Result<TransformR
     let syntax = syntax_from_option(&context.module_type);
 
     let source_file_name = Arc::new(FileName::Real(Path::new(&filename).to_path_buf()));
-    let source_map = Arc::new(swc_common::SourceMap::default());
     let source_file = source_map.new_source_file_from(source_file_name, content.clone());
 
     // parse
     let mut module = parse(&source_file, syntax)?;
 
     // optimize
+    let property_regex = context.options.property_regex()?;
+    let mut renamed = Ok(FxHashMap::default());
+    let mut hoisted = FxHashMap::default();
+
     try_with(source_map.clone(), &context.globals.clone(), || {
-        object_member_minify(&mut module, &context);
+        if let Some(regex) = &property_regex {
+            renamed = mangle_properties(&mut module, &context, regex);
+        }
+
+        if renamed.is_ok() {
+            hoisted = object_member_minify(&mut module, &context);
+        }
     })?;
 
+    let renamed = renamed?;
+
     let mut src = if context.options.source_map.is_some() || context.options.enable_source_map {
         Some(vec![])
     } else {
@@ -340,12 +890,83 @@ pub fn transform(content: String, options: TransformOption) -> Result<TransformR
     };
 
     // codegen
-    let code = codegen(&mut module, source_map.clone(), src.as_mut())?;
+    let code = codegen(
+        &mut module,
+        source_map.clone(),
+        src.as_mut(),
+        context.options.ascii_only,
+    )?;
+
+    let output = String::from_utf8_lossy(&code).to_string();
+
+    // the input is not an acceptable output when it has to be escaped, or when the other
+    // chunks rename its properties
+    let can_fallback = !context.options.allow_larger
+        && (!context.options.ascii_only || content.is_ascii())
+        && context.options.mangle_properties.is_none();
+
+    let fallback = can_fallback
+        .then(|| fallback_reason(&content, &output, context.options.objective))
+        .flatten();
+
+    let name_cache = context.options.name_cache.clone().map(|mut cache| {
+        // nothing is hoisted when the input is returned
+        if fallback.is_none() {
+            for (value, name) in hoisted {
+                cache.entry(value).or_insert(name);
+            }
+        }
+
+        cache
+    });
+
+    let property_cache = context.options.mangle_properties.as_ref().map(|option| {
+        let mut cache = option.name_cache.clone().unwrap_or_default();
+
+        for (property, name) in renamed {
+            cache.entry(property).or_insert(name);
+        }
+
+        cache
+    });
+
+    let (content, map) = match fallback {
+        // the input is returned untouched
+        Some(_) => (content.to_string(), context.options.source_map),
+        None => {
+            let map = try_build_output_sourcemap(source_map, context.options.source_map, src)?;
 
-    let content = String::from_utf8_lossy(&code).to_string();
-    let map = try_build_output_sourcemap(source_map, context.options.source_map, src)?;
+            (output, map)
+        }
+    };
+
+    let size_budget = context
+        .options
+        .size_budget
+        .map(|budget| SizeBudgetResult::new(budget, content.len()));
+
+    Ok(TransformResult {
+        content,
+        map,
+        size_budget,
+        fallback,
+        name_cache,
+        property_cache,
+    })
+}
+
+fn fallback_reason(input: &str, output: &str, objective: CompressObjective) -> Option<Fallback> {
+    if output.len() >= input.len() {
+        return Some(Fallback::RawSizeNotReduced);
+    }
 
-    Ok(TransformResult { content, map })
+    if !matches!(objective, CompressObjective::Raw)
+        && objective.compressed_len(output.as_bytes()) >= objective.compressed_len(input.as_bytes())
+    {
+        return Some(Fallback::CompressedSizeNotReduced);
+    }
+
+    None
 }
 
 #[cfg(test)]
@@ -452,6 +1073,111 @@ require.async(a);"#,
         Ok(())
     }
 
+    #[test]
+    fn reserved_patterns_taking_every_name() {
+        let options = TransformOption {
+            reserved: ReservedOption {
+                patterns: vec![".*".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(transform("foo.bar;".to_string(), options).is_err());
+    }
+
+    #[test]
+    fn size_budget() -> Result<()> {
+        let input = r#"
+el.classList.add("active");
+el.classList.remove("active");
+el.classList.toggle("active");
+el.classList.contains("active");
+"#;
+
+        let transform_with_budget = |size_budget: usize| {
+            transform(
+                input.to_string(),
+                TransformOption {
+                    size_budget: Some(size_budget),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // fits without hoisting
+        let result = transform_with_budget(1000)?;
+
+        assert_eq!(result.content.trim(), input.trim());
+        assert_eq!(
+            result.size_budget,
+            Some(SizeBudgetResult {
+                budget: 1000,
+                size: result.content.len(),
+                met: true,
+                remaining: 1000 - result.content.len() as isize,
+            })
+        );
+
+        // `active` alone is enough
+        let result = transform_with_budget(input.trim().len() - 5)?;
+
+        assert!(result.content.contains("el.classList.add(a)"));
+        assert!(result.size_budget.is_some_and(|v| v.met));
+
+        // can not fit, everything is hoisted
+        let result = transform_with_budget(10)?;
+
+        assert!(result.content.contains("el[b].add(a)"));
+        assert!(result
+            .size_budget
+            .is_some_and(|v| !v.met && v.remaining < 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fallback() -> Result<()> {
+        // reprinting alone grows it
+        let input = "if(a){b()}";
+
+        let result = transform(input.to_string(), Default::default())?;
+
+        assert_eq!(result.content, input);
+        assert_eq!(result.fallback, Some(Fallback::RawSizeNotReduced));
+
+        let result = transform(
+            input.to_string(),
+            TransformOption {
+                allow_larger: true,
+                ..Default::default()
+            },
+        )?;
+
+        assert_ne!(result.content, input);
+        assert_eq!(result.fallback, None);
+
+        let input = "a.somethingLong;".repeat(8);
+
+        let result = transform(input.clone(), Default::default())?;
+
+        assert_eq!(result.fallback, None);
+
+        // gzip already dedupes `a.somethingLong;`
+        let result = transform(
+            input.clone(),
+            TransformOption {
+                objective: CompressObjective::Gzip,
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(result.content, input);
+        assert!(result.fallback.is_some());
+
+        Ok(())
+    }
+
     #[test]
     fn require() -> Result<()> {
         let input = r#"
@@ -481,4 +1207,131 @@ require("./foo.js");
 
         Ok(())
     }
+
+    #[test]
+    fn name_cache() -> Result<()> {
+        let options = |name_cache| TransformOption {
+            name_cache: Some(name_cache),
+            allow_larger: true,
+            ..Default::default()
+        };
+
+        let input = format!("{}{}", "a.foooooo;".repeat(4), "a.baaaaaar;".repeat(3));
+        let result = transform(input.clone(), options(BTreeMap::new()))?;
+        let name_cache = result.name_cache.unwrap();
+
+        assert_eq!(name_cache.len(), 2);
+
+        // a new string, used more than the cached ones, does not take their names
+        let input = format!("{}{}", "a.quuuuuux;".repeat(5), input);
+        let result = transform(input.clone(), options(name_cache.clone()))?;
+        let next = result.name_cache.unwrap();
+
+        assert_eq!(next.len(), 3);
+        assert_eq!(next["foooooo"], name_cache["foooooo"]);
+        assert_eq!(next["baaaaaar"], name_cache["baaaaaar"]);
+        assert!(!name_cache.values().contains(&next["quuuuuux"]));
+
+        // the cached names are kept when nothing is hoisted
+        let result = transform("a.b;".to_string(), options(next.clone()))?;
+
+        assert_eq!(result.name_cache.unwrap(), next);
+
+        Ok(())
+    }
+
+    #[test]
+    fn mangle_properties() -> Result<()> {
+        let options = |name_cache| TransformOption {
+            mangle_properties: Some(ManglePropertiesOption {
+                regex: "^_".to_string(),
+                reserved: vec!["_keep".to_string()],
+                name_cache,
+            }),
+            ..Default::default()
+        };
+
+        let input = "const o = { _foo: 1, _bar: 2, _keep: 3, _str: 4 };
+            const { _bar } = o;
+            o._foo + o[\"_foo\"] + o._keep + (\"_str\" in o);";
+        let result = transform(input.to_string(), options(None))?;
+        let cache = result.property_cache.unwrap();
+
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache["_foo"], "a");
+        assert_eq!(cache["_bar"], "b");
+        // the other chunks leave it too
+        assert_eq!(cache["_str"], "_str");
+        assert!(result.content.contains("const { b: _bar } = o;"));
+        assert!(result
+            .content
+            .contains("o.a + o.a + o._keep + (\"_str\" in o)"));
+
+        // the cached names are kept, `a` is taken by a property left as it is
+        let result = transform(
+            "o._bar + o._baz + o.a;".to_string(),
+            options(Some(cache.clone())),
+        )?;
+        let next = result.property_cache.unwrap();
+
+        assert_eq!(next["_bar"], "b");
+        assert_eq!(next["_baz"], "c");
+        assert!(result.content.contains("o.b + o.c + o.a"));
+
+        // another chunk decided it, a string no longer keeps `_foo`, and `_str` is kept
+        let result = transform(
+            "o._foo + (\"_foo\" in o) + o._str;".to_string(),
+            options(Some(cache.clone())),
+        )?;
+
+        assert!(result.content.contains("o.a + (\"a\" in o) + o._str"));
+
+        // only the strings naming it are renamed
+        let result = transform(
+            "o[\"_foo\"] + ({ [\"_foo\"]: 1 }); throw new Error(\"_foo\");".to_string(),
+            options(Some(cache.clone())),
+        )?;
+
+        assert!(result.content.contains("o.a + ({\n    a: 1\n})"));
+        assert!(result.content.contains("throw new Error(\"_foo\")"));
+
+        // `a` is a property left as it is here, `_foo` can not take it
+        assert!(transform(
+            "o._foo + o.a;".to_string(),
+            options(Some(BTreeMap::from([(
+                "_foo".to_string(),
+                "a".to_string()
+            )]))),
+        )
+        .is_err());
+
+        let mut invalid = options(None);
+
+        invalid.mangle_properties.as_mut().unwrap().regex = "(".to_string();
+
+        assert!(transform(input.to_string(), invalid).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reserved() -> Result<()> {
+        let input = "a.somethingLong;".repeat(8);
+        let options = |patterns: &[&str], preserve_keywords: &[&str]| TransformOption {
+            reserved: ReservedOption {
+                patterns: patterns.iter().map(|v| v.to_string()).collect(),
+                ..Default::default()
+            },
+            preserve_keywords: preserve_keywords.iter().map(|v| v.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let result = transform(input.clone(), options(&["^[a-z]$"], &["A"]))?;
+
+        assert!(result.content.starts_with("var B = \"somethingLong\""));
+
+        assert!(transform(input, options(&["("], &[])).is_err());
+
+        Ok(())
+    }
 }
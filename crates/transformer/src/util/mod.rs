@@ -5,7 +5,10 @@ use swc_common::{
     errors::HANDLER, BytePos, Globals, LineCol, Mark, SourceMap, SyntaxContext, GLOBALS,
 };
 use swc_common::{source_map::SourceMapGenConfig, FileName};
-use swc_ecma_ast::Module;
+use swc_ecma_ast::{
+    Decl, Expr, ExprStmt, Lit, MemberExpr, Module, ModuleItem, Stmt, Str, Tpl, VarDecl,
+    VarDeclKind, VarDeclarator,
+};
 use swc_ecma_transforms::{
     helpers::{Helpers, HELPERS},
     resolver,
@@ -57,6 +60,116 @@ where
     })
 }
 
+/// the string of `"use strict";`, a directive when it starts a module or a function body
+fn directive(stmt: &Stmt) -> Option<&Str> {
+    match stmt {
+        Stmt::Expr(ExprStmt {
+            expr: box Expr::Lit(Lit::Str(str)),
+            ..
+        }) => Some(str),
+        _ => None,
+    }
+}
+
+/// the directives of a function body, e.g. `"use strict"`
+pub fn function_prologue(stmts: &[Stmt]) -> impl Iterator<Item = &Str> {
+    stmts.iter().map_while(directive)
+}
+
+///
+/// The directives of a module, e.g. `"use strict"` or `"use client"`, the hoisted
+/// declarations are inserted after them.
+///
+pub fn module_prologue(module: &Module) -> impl Iterator<Item = &Str> {
+    module
+        .body
+        .iter()
+        .map_while(|item| item.as_stmt().and_then(directive))
+}
+
+///
+/// Declares `decls` with a `var` after the prologue of `module`, before the ones hoisted
+/// earlier.
+///
+/// The passes hoisting declarations share one `var`, found by its dummy span, a parsed one
+/// always has a real span. The declarators run in order, so the earlier ones are still
+/// declared before the ones hoisted earlier read them.
+///
+pub fn declare_hoisted(module: &mut Module, mut decls: Vec<VarDeclarator>) {
+    if decls.is_empty() {
+        return;
+    }
+
+    let pos = module_prologue(module).count();
+
+    if let Some(ModuleItem::Stmt(Stmt::Decl(Decl::Var(var)))) = module.body.get_mut(pos)
+        && var.kind == VarDeclKind::Var
+        && var.span.is_dummy()
+    {
+        decls.append(&mut var.decls);
+        var.decls = decls;
+        return;
+    }
+
+    // a directive only counts at the top, e.g. `"use client"`
+    module.body.insert(
+        pos,
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: Default::default(),
+            ctxt: Default::default(),
+            kind: VarDeclKind::Var,
+            declare: false,
+            decls,
+        })))),
+    );
+}
+
+/// the string of `` `foo` ``, a template without substitutions
+pub fn tpl_as_str(tpl: &Tpl) -> Option<&str> {
+    match tpl.quasis.as_slice() {
+        [quasi] if tpl.exprs.is_empty() => quasi.cooked.as_deref(),
+        _ => None,
+    }
+}
+
+///
+/// The object `a.b.c` is read from and the members reading each of its prefixes, `a.b`
+/// first, whatever their properties are.
+///
+pub fn member_chain(member: &MemberExpr) -> (&Expr, Vec<&MemberExpr>) {
+    let mut members = vec![member];
+    let mut obj = &*member.obj;
+
+    while let Expr::Member(member) = obj {
+        members.push(member);
+        obj = &member.obj;
+    }
+
+    members.reverse();
+
+    (obj, members)
+}
+
+///
+/// The constant as it is written, `0xff`, `10n` or `/foo/i`, the identical ones share a
+/// hoisted variable.
+///
+/// A regex with the `g` or `y` flag keeps its `lastIndex`, sharing one changes how it
+/// matches, so it is never a constant. The others are only constants where they can not
+/// escape, e.g. `/foo/i.test(a)`, see [`crate::collector::IdentCollector`].
+///
+pub fn constant_key(lit: &Lit) -> Option<String> {
+    match lit {
+        Lit::Num(num) => num.raw.as_ref().map(|raw| raw.to_string()),
+        Lit::BigInt(big_int) => big_int.raw.as_ref().map(|raw| raw.to_string()),
+        Lit::Regex(regex) if !regex.flags.contains(['g', 'y']) => {
+            Some(format!("/{}/{}", regex.exp, regex.flags))
+        }
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
 struct SourceMapConfig;
 
 impl SourceMapGenConfig for SourceMapConfig {
@@ -65,6 +178,7 @@ impl SourceMapGenConfig for SourceMapConfig {
     }
 }
 
+#[allow(dead_code)]
 pub fn build_source_map(
     cm: Arc<SourceMap>,
     mappings: &[(BytePos, LineCol)],
@@ -16,8 +16,11 @@ pub fn codegen(
     module: &mut Module,
     cm: Arc<SourceMap>,
     src_map: Option<&mut Vec<(BytePos, LineCol)>>,
+    ascii_only: bool,
 ) -> Result<Vec<u8>> {
-    let config = Config::default().with_omit_last_semi(true);
+    let config = Config::default()
+        .with_omit_last_semi(true)
+        .with_ascii_only(ascii_only);
     let mut buf = vec![];
     let writer = Box::new(JsWriter::new(cm.clone(), "\n", &mut buf, src_map)) as Box<dyn WriteJs>;
 
@@ -69,8 +72,7 @@ pub fn try_build_output_sourcemap(
 
     // SourceMap::from(value)
     let input_src = input_src
-        .map(|s| sourcemap::SourceMap::from_slice(s.as_bytes()).ok())
-        .flatten();
+        .and_then(|s| sourcemap::SourceMap::from_slice(s.as_bytes()).ok());
 
     let source_map =
         source_map.build_source_map_with_config(&src, input_src.as_ref(), SourceMapConfig {});
@@ -0,0 +1,49 @@
+class Base {
+  constructor() {
+    this.initialized = true;
+  }
+
+  veryLongMethodName() {
+    return this.initialized;
+  }
+
+  get accessorValue() {
+    return this.veryLongMethodName();
+  }
+
+  set accessorValue(value) {
+    this.initialized = value;
+  }
+
+  static staticFactory() {
+    return new Base();
+  }
+
+  static staticCounter = 0;
+
+  instanceField = 1;
+}
+
+class Derived extends Base {
+  constructor() {
+    super();
+    this.instanceField = super.veryLongMethodName();
+  }
+
+  veryLongMethodName() {
+    return super.veryLongMethodName() && super["accessorValue"];
+  }
+}
+
+const derived = Derived.staticFactory();
+
+derived.veryLongMethodName();
+derived.accessorValue = derived.accessorValue;
+Base.staticCounter++;
+Base.staticCounter++;
+derived.instanceField;
+x.constructor;
+x.constructor;
+x.constructor;
+x.constructor;
+x.constructor;
@@ -0,0 +1,27 @@
+// written once, not worth hoisting, printed as `config.timeout`
+config["timeout"];
+
+const headers = { "accept": "json", ["cache"]: "no-store", "x-id": 1 };
+
+// keep their meaning as computed keys
+const proto = { ["__proto__"]: null };
+
+class Base {
+  ["constructor"]() {}
+  static ["prototype"] = 1;
+}
+
+class Child extends Base {
+  get() {
+    return super["get"]();
+  }
+}
+
+// worth hoisting, `.someLongKey` is the cost it is measured against
+el["someLongKey"];
+el["someLongKey"];
+el["someLongKey"];
+el["someLongKey"];
+el.someLongKey;
+el.someLongKey;
+const { "someLongKey": value } = el;
@@ -0,0 +1,26 @@
+// identical numbers, bigints and regexes share a variable
+export function hash(value) {
+  let h = 0x811c9dc5;
+  for (let i = 0; i < value.length; i++) {
+    h = Math.imul(h ^ value.charCodeAt(i), 0x01000193) & 0xffffffff;
+  }
+  return (h & 0xffffffff) >>> 0 === 0x811c9dc5 ? 0x01000193 : h;
+}
+
+export const epsilon = [0.0001220703125, 0.0001220703125, 0.0001220703125];
+export const big = [9007199254740993n, 9007199254740993n];
+
+export const isUpper = (s) => /^[A-Z][A-Z0-9_]*$/.test(s);
+export const isConstant = (s) => /^[A-Z][A-Z0-9_]*$/.test(s.trim());
+
+// stored, passed or changed, each usage keeps its own regex
+export const upper = /^[A-Z][A-Z0-9_]*$/;
+export const split = (s) => s.split(/^[A-Z][A-Z0-9_]*$/);
+/^[A-Z][A-Z0-9_]*$/.lastIndex = 1;
+
+// `g` and `y` keep a `lastIndex`, these are left as they are
+export const words = (s) => s.match(/[a-z][a-z0-9_]*/g);
+export const first = (s) => s.match(/[a-z][a-z0-9_]*/g)[0];
+
+// written like the string, left as it is
+export const id = [4294967295, 4294967295, "4294967295", "4294967295"];
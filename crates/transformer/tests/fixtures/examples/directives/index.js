@@ -0,0 +1,14 @@
+"use client";
+"use strict";
+
+export function Button(props) {
+  "use strict";
+  return props.variant === "primary" ? "button button-primary" : "button";
+}
+
+export const label = (variant) => {
+  "use strict";
+  return variant === "primary" ? "primary" : "secondary";
+};
+
+export const variants = ["primary", "secondary", "primary", "secondary", "use strict", "use strict"];
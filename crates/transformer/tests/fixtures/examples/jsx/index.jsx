@@ -0,0 +1,20 @@
+// attribute names are never rewritten, string values become `{a}`
+export const Card = ({ title }) => (
+  <section className="card-container" data-title={title}>
+    <header className="card-container">{title}</header>
+    <Content className="card-container" tooltip="card-container" />
+    <footer className="card-container" />
+  </section>
+);
+
+// `<section>` could only become a capitalized name, `a` would be another element
+export const List = ({ items }) => (
+  <section>
+    {items.map((item) => (
+      <section key={item.id}>{item.label}</section>
+    ))}
+  </section>
+);
+
+el.className;
+el.className;
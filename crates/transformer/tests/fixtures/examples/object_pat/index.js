@@ -0,0 +1,13 @@
+// `{ someLongOption }` => `{ [a]: someLongOption }` would grow, so it is kept
+const { someLongOption, anotherOption = 1, ...rest } = props;
+
+function render({ someLongOption: option, anotherOption: another = 2 }, [{ anotherOption: renamed }]) {
+  return option + another + renamed;
+}
+
+({ someLongOption: x, anotherOption: y } = props);
+
+const { someLongOption: first, ...others } = props;
+
+props.someLongOption;
+props.anotherOption;
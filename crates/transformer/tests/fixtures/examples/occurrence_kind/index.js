@@ -0,0 +1,26 @@
+const options = { padding, top: 1, "padding-top": 2 };
+
+// `{ padding }` would grow, so it is kept
+use({ padding });
+use({ padding });
+use({ padding });
+
+// `top:` => `[a]:` saves nothing, so it is kept
+use({ top: 1 });
+use({ top: 2 });
+
+el.padding;
+el.padding;
+el.padding;
+el.top;
+el.top;
+el.top;
+el.top;
+el.top;
+el.top;
+el.top;
+el.top;
+el.top;
+el.top;
+el["padding-top"];
+el["padding-top"];
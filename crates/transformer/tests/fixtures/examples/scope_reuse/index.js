@@ -0,0 +1,13 @@
+function sum(a, b) {
+  return a + b;
+}
+
+function update(a) {
+  a.classList.add("active");
+  a.classList.remove("active");
+  a.classList.toggle("active");
+}
+
+el.classList.add("active");
+el.classList.remove("active");
+el.classList.toggle("active");
@@ -0,0 +1,14 @@
+// no substitutions, the same as a string
+el.className = `someClassName`;
+el.className = "someClassName";
+el.className = `someClassName`;
+
+// quasis of untagged templates
+log(`request failed: ${url}`);
+log(`request failed: ${url} (${status})`);
+log(`request failed: ${url}, retrying`);
+
+// tagged templates see the raw strings, they are left as they are
+css`someClassName`;
+css`request failed: ${url}`;
+css`request failed: ${"someClassName"}`;
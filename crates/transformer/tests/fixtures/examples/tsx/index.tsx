@@ -0,0 +1,13 @@
+interface Props {
+  variant: "primary-button" | "secondary-button";
+}
+
+export function Button<T extends Props>({ variant }: T) {
+  return (
+    <button className="primary-button" data-variant={variant as string}>
+      {variant === "primary-button" ? <span className="primary-button" /> : null}
+    </button>
+  );
+}
+
+export const isPrimary = (props: Props) => props.variant === "primary-button";
@@ -0,0 +1,65 @@
+export class Profile {
+  render() {
+    const name = this.props.store.state.user.name;
+    const email = this.props.store.state.user.email;
+    const avatar = this.props.store.state.user.avatar;
+    const plan = this.props.store.state.user.plan;
+
+    return format(name, email, avatar, plan, this.props.store.state.user.id);
+  }
+
+  // the call may change the user between the reads
+  refresh() {
+    const before = this.props.store.state.user.updatedAt;
+    this.props.store.reload();
+    const after = this.props.store.state.user.updatedAt;
+    const again = this.props.store.state.user.updatedAt;
+    return before !== after && after === again;
+  }
+
+  // the first read may not run, so the chain is left as it is
+  guard(options) {
+    if (options.enabled) {
+      return options.settings.display.theme.primary + options.settings.display.theme.accent;
+    }
+    return options.settings.display.theme.primary || options.settings.display.theme.fallback;
+  }
+
+  // `user` is written between the reads, and the write may run a setter changing the rest
+  rename(value) {
+    const first = this.props.store.state.user.first;
+    this.props.store.state.user = { first: value };
+    return first + this.props.store.state.user.first + this.props.store.state.user.last;
+  }
+
+  // `value` may be a setter replacing `this.state`
+  assign(next) {
+    const name = this.state.user.name;
+    this.value = next;
+    return name + this.state.user.id + this.state.user.email;
+  }
+
+  // `${tag}` may run a `toString` replacing `this.state`
+  describe(tag) {
+    return `${tag}: ${this.state.user.name}` + this.state.user.id;
+  }
+
+  // the loop runs the call again
+  each(items) {
+    const out = [];
+    for (let i = 0; i < items.length; i++) {
+      out.push(this.config.layout.columns.width * i + this.config.layout.columns.gap);
+    }
+    return out;
+  }
+
+  bind() {
+    // `this.props.handlers` is called with its object, so only the object is aliased
+    this.props.handlers.events.onOpen(this.props.handlers.events.name);
+  }
+}
+
+export function sum(point) {
+  "use strict";
+  return [point.position.vector.x, point.position.vector.y, point.position.vector.z];
+}
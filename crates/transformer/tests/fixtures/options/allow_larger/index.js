@@ -0,0 +1 @@
+if(a){b.foo()}else{b.bar()}
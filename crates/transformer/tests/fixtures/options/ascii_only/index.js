@@ -0,0 +1,3 @@
+obj.中文 = 1;
+console.log(obj.中文);
+console.log(obj.中文);
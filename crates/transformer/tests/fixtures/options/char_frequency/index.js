@@ -0,0 +1,15 @@
+// appears first, but used less
+obj.firstProperty;
+obj.firstProperty;
+obj.firstProperty;
+
+for (let i = 0; i < 10; i++) {
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+}
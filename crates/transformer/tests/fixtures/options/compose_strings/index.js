@@ -0,0 +1,15 @@
+const Button = require("./components/Button");
+const Input = require("./components/Input");
+const Select = require(`./components/Select`);
+const Modal = require("./components/Modal");
+
+// the module specifiers can not be replaced
+import Layout from "./components/Layout";
+export * from "./components/Layout";
+
+load("./components/Checkbox").then(loaded);
+"./components/Tooltip".length;
+
+register("userController");
+register("orderController");
+register("itemController");
@@ -0,0 +1,12 @@
+import a from "./a";
+import b from "./b";
+
+load("./", a);
+load("./", b);
+load("./", a);
+load("./", b);
+
+el.classList.add("active");
+el.classList.add("active");
+el.classList.remove("active");
+el.classList.remove("active");
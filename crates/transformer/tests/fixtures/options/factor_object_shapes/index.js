@@ -0,0 +1,21 @@
+export const columns = [
+  { id: 1, name: "Name", type: "string", width: 120 },
+  { id: 2, name: "Age", type: "number", width: 80 },
+  { id: 3, name: "Email", type: "string", width: 200 },
+  { id: 4, name: "Created", type: "date", width: 160 },
+];
+
+// the nested literals have their own shape
+export const points = [
+  { x: 0, y: 0, meta: { id: 5, name: "origin", type: "point", width: 0 } },
+  { x: 1, y: -1, meta: { id: 6, name: "corner", type: "point", width: 0 } },
+  { x: -1, y: 1, meta: {} },
+];
+
+// values with side effects, spreads and getters are left as they are
+export const rows = [
+  { id: next(), name: "a", type: "row", width: 10 },
+  { ...base, id: 8, name: "b", type: "row", width: 10 },
+  { get id() { return 9; }, name: "c", type: "row", width: 10 },
+  { id: 10, width: 10, name: "d", type: "row" },
+];
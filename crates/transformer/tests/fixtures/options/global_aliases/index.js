@@ -0,0 +1,40 @@
+export function define(target, props) {
+  for (const key of Object.keys(props)) {
+    Object.defineProperty(target, key, { value: props[key] });
+  }
+  Object.defineProperty(target, "size", { value: Object.keys(props).length });
+  Object.defineProperty(target, "created", { value: Date.now() });
+}
+
+// called chains keep `this`, read when the module is evaluated
+export const root = document.createElement("main");
+export function render(tag) {
+  const el = document.createElement(tag);
+  el.appendChild(document.createElement("span"));
+  el.appendChild(document.createElement("span"));
+  return el;
+}
+
+// a value is not bound, `call` still sees the same function
+export function toArray(list) {
+  return Array.prototype.slice.call(list);
+}
+export const head = (list) => Array.prototype.slice.call(list, 0, 1);
+export const tail = (list) => Array.prototype.slice.call(list, 1);
+
+// shadowed, not the global
+export function local(window) {
+  return [window.localStorage, window.localStorage, window.localStorage];
+}
+
+// assigned, left as it is
+JSON.parse = (v) => v;
+export const parse = [JSON.parse, JSON.parse, JSON.parse, JSON.parse];
+
+// only read in a function, `document` may not exist when the module is evaluated
+export function mount(el) {
+  if (typeof document !== "undefined") {
+    document.body.appendChild(el);
+    document.body.appendChild(document.body.firstChild);
+  }
+}
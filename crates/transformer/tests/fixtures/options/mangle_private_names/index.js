@@ -0,0 +1,52 @@
+export class Cache {
+  #veryLongCacheField = new Map();
+  #hits = 0;
+  static #instances = 0;
+
+  constructor() {
+    Cache.#instances++;
+  }
+
+  get #size() {
+    return this.#veryLongCacheField.size;
+  }
+
+  #touch(key) {
+    this.#hits++;
+    return this.#veryLongCacheField.get(key);
+  }
+
+  get(key) {
+    return this.#veryLongCacheField.has(key) ? this.#touch(key) : undefined;
+  }
+
+  static isCache(obj) {
+    return #veryLongCacheField in obj && obj.#size >= 0;
+  }
+
+  // the nested class declares its own `#hits`, and refers to the outer `#veryLongCacheField`
+  child() {
+    const outer = this;
+    return new (class {
+      #hits = 1;
+      #extra = 2;
+      read(key) {
+        return outer.#veryLongCacheField.get(key) ?? this.#hits + this.#extra;
+      }
+    })();
+  }
+
+  // `extends` is evaluated outside the nested class, `#touch` there is the one of `Cache`
+  derived(key) {
+    return class extends this.#touch(key) {
+      #touch = 1;
+      #extra = 2;
+      read() {
+        return this.#touch + this.#extra;
+      }
+    };
+  }
+}
+
+// a public member with the same name is left as it is
+export const plain = { veryLongCacheField: 1 };
@@ -0,0 +1,39 @@
+class Store {
+  _items = [];
+  _cache = new Map();
+
+  _lookup(key) {
+    return this._cache.get(key);
+  }
+
+  get _size() {
+    return this._items.length;
+  }
+
+  add(item) {
+    this._items.push(item);
+    this._cache.set(item._id, item);
+  }
+}
+
+class CountingStore extends Store {
+  _lookup(key) {
+    this._hits = (this._hits || 0) + 1;
+    return super._lookup(key);
+  }
+}
+
+export function createItem(_id, label) {
+  return { _id, label, _internal: true };
+}
+
+export function describe({ _id, label, _internal = false }) {
+  return `${_id}:${label}:${_internal}`;
+}
+
+export function isItem(value) {
+  // also written as a string, left as it is
+  return "_legacy" in value || value._legacy === true;
+}
+
+export { Store, CountingStore };
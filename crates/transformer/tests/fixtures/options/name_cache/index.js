@@ -0,0 +1,16 @@
+// cached, keeps its name
+obj.firstProperty;
+obj.firstProperty;
+obj.firstProperty;
+
+// not cached, skips the cached names
+for (let i = 0; i < 10; i++) {
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+  obj.used;
+}
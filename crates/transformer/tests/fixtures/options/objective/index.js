@@ -0,0 +1,6 @@
+window.localStorage.setItem("token", value);
+window.localStorage.getItem("token");
+window.localStorage.removeItem("token");
+document.documentElement.classList.add("dark");
+document.documentElement.classList.remove("dark");
+document.documentElement.classList.toggle("dark");
@@ -0,0 +1,6 @@
+obj.firstProperty;
+obj.firstProperty;
+obj.firstProperty;
+obj.secondProperty;
+obj.secondProperty;
+obj.secondProperty;
@@ -0,0 +1,6 @@
+el.classList.add("active");
+el.classList.remove("active");
+el.classList.toggle("active");
+el.classList.contains("active");
+el.classList.add("hidden");
+el.classList.remove("hidden");
@@ -87,6 +87,7 @@ fn format_output_name(file_path: PathBuf) -> PathBuf {
 
 fn test_factory(config: FixtureConfig) -> Result<()> {
     let content = read_to_string(&config.file)?;
+    let source = config.file;
     let files = try_read_config_files(config.cwd)?;
 
     for file in files {
@@ -95,7 +96,8 @@ fn test_factory(config: FixtureConfig) -> Result<()> {
         let output = omm_transformer::transform(
             content.clone(),
             TransformOption {
-                filename: Some(file.to_string_lossy().to_string()),
+                // the module type follows the extension of the fixture
+                filename: Some(source.to_string_lossy().to_string()),
                 ..config.clone()
             },
         )?;
@@ -155,4 +157,10 @@ fn options() -> Result<()> {
 #[test]
 fn examples() -> Result<()> {
     fixtures_factor("tests/fixtures/examples/**/*.js")
-}
\ No newline at end of file
+}
+
+#[test]
+fn jsx_examples() -> Result<()> {
+    fixtures_factor("tests/fixtures/examples/**/*.jsx")?;
+    fixtures_factor("tests/fixtures/examples/**/*.tsx")
+}
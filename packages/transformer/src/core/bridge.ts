@@ -68,7 +68,8 @@ export interface StringLitOption {
     /**
      * match string literal
      *
-     * - `content`: `"use strict"` => ignore collect `"use strict"`
+     * - `content`: `"page-view"` => ignore collect `"page-view"`, the directives such as
+     *   `"use strict"` are always skipped
      **/
     type: "stringLit";
     content: string;
@@ -76,6 +77,51 @@ export interface StringLitOption {
 
 export type IgnoreWord = string | IgnoreWordObject | StringLitOption;
 
+export type CompressObjective = "raw" | "gzip" | "brotli";
+
+export type CostPreset = "conservative" | "balanced" | "aggressive";
+
+export interface CostStrategy {
+    /**
+     * - `conservative`: at least 4 bytes long, used 3 times, saves 10 bytes
+     * - `balanced`: at least 3 bytes long, used 2 times, saves 1 byte
+     * - `aggressive`: used 2 times, saves 1 byte
+     * @default "balanced"
+     */
+    preset?: CostPreset;
+    /**
+     * minimum utf-8 bytes of a string, overrides the preset
+     */
+    minLength?: number;
+    /**
+     * minimum usages of a string, overrides the preset
+     */
+    minOccurrences?: number;
+    /**
+     * minimum bytes saved by hoisting a string, overrides the preset
+     */
+    minSavedBytes?: number;
+}
+
+export type EnvProfile = "browser" | "worker" | "node" | "miniProgram";
+
+export interface ReservedOption {
+    /**
+     * the environments running the output, their globals are never taken.
+     *
+     * - `browser`: `window`, `name`, `top`, `self`, `event`, ... and `$`, `_`
+     * - `worker`: `self`, `name`, `importScripts`, ... and `$`, `_`
+     * - `node`: `require`, `module`, `exports`, `process`, ...
+     * - `miniProgram`: `wx`, `App`, `Page`, `getApp`, ... and `$`, `_`
+     */
+    profiles?: EnvProfile[];
+    names?: string[];
+    /**
+     * regular expressions, a name matching any of them is reserved
+     */
+    patterns?: string[];
+}
+
 export interface TransformOption {
     filename?: string;
     /**
@@ -92,12 +138,180 @@ export interface TransformOption {
      * ignore words in the code
      */
     ignoreWords?: IgnoreWord[];
+    /**
+     * names a hoisted variable must not take, added to `reserved.names`
+     */
     preserveKeywords?: string[];
+    /**
+     * the names a hoisted variable must not take, besides the javascript reserved words.
+     *
+     * the hoisted variables are declared at the top level, select the profiles of the
+     * environments running the output so their globals are not overwritten
+     * @default {}
+     */
+    reserved?: ReservedOption;
+    /**
+     * the size to reduce when deciding which strings to hoist.
+     *
+     * `gzip` and `brotli` print and compress the chunk several times, slower than `raw`,
+     * dropping the strings that make it larger on a best-effort basis
+     * @default "raw"
+     */
+    objective?: CompressObjective;
+    /**
+     * escape non-ascii characters in the output
+     * @default false
+     */
+    asciiOnly?: boolean;
+    /**
+     * the thresholds a string must pass to be hoisted
+     * @default { preset: "balanced" }
+     */
+    costStrategy?: CostStrategy;
+    /**
+     * the bytes the output should fit in, hoisting stops once it fits.
+     *
+     * see `TransformResult.sizeBudget` for whether it fits
+     */
+    sizeBudget?: number;
+    /**
+     * return the output even when it is not smaller than the input,
+     * otherwise the input and its source map are returned untouched.
+     *
+     * the input is always transformed when `asciiOnly` is set and it has non-ascii characters
+     * @default false
+     */
+    allowLarger?: boolean;
+    /**
+     * order the characters of the hoisted names by how often they appear in the chunk,
+     * so the names reuse the characters common in it and compress better
+     * @default false
+     */
+    charFrequency?: boolean;
+    /**
+     * hoist the prefixes and suffixes shared by strings not worth hoisting on their own,
+     * e.g. `"./components/Button"` => `a+"Button"`
+     * @default false
+     */
+    composeStrings?: boolean;
+    /**
+     * the names hoisted strings took in previous builds, string => name.
+     *
+     * a string keeps its name when it is hoisted again, new strings never take a cached name.
+     * the updated cache is returned in `TransformResult.nameCache`
+     */
+    nameCache?: Record<string, string>;
+    /**
+     * the member chains of globals aliased by a hoisted variable when repeated,
+     * e.g. `"Object.defineProperty"` or `"document.createElement"`, a called chain is bound
+     * to its object.
+     *
+     * the chains are read once when the chunk is evaluated instead of at each usage, only list
+     * the ones that exist by then and never change. a chain of a host global, e.g. `document`
+     * or `window`, is only aliased when the chunk already reads it when evaluated, outside any
+     * function, branch or `typeof`, so a chunk loaded where it does not exist, e.g. in a worker
+     * or on the server, does not throw. a chain of a global of the language, e.g. `Object`, is
+     * always read when evaluated, a missing member throws when it is bound
+     * @default []
+     */
+    globalAliases?: string[];
+    /**
+     * create the object literals sharing their keys with a hoisted factory when smaller,
+     * e.g. `{ id: 1, name: "x" }` => `a(1, "x")`.
+     *
+     * only the literals with plain keys and values without side effects are created by a
+     * factory
+     * @default false
+     */
+    factorObjectShapes?: boolean;
+    /**
+     * rename the private fields, methods and accessors of each class,
+     * e.g. `#veryLongCacheField` => `#a`, nothing is renamed when the chunk calls `eval`
+     * @default false
+     */
+    manglePrivateNames?: boolean;
+    /**
+     * rename the properties matching `regex` in member expressions, object literals,
+     * classes and destructuring, e.g. `this._cache` => `this.a`.
+     *
+     * only select the properties never read from outside the output, a property also
+     * written as a string or naming a jsx attribute is left as it is, unless
+     * `mangleProperties.nameCache` renames it. The output is returned even when it is not smaller
+     * @default undefined
+     */
+    mangleProperties?: ManglePropertiesOption;
+    /**
+     * alias the member chains read repeatedly in a function with a `const` declared in it,
+     * e.g. `this.props.store.state.user` => `a`, when nothing between the reads may write them.
+     *
+     * reading a property is assumed to have no side effect, the prefixes of an aliased chain
+     * are read once, so do not enable it when a getter on them does more than return a value.
+     * a call, a write to any property or a conversion like `obj + ""` ends the reads sharing
+     * the alias
+     * @default false
+     */
+    aliasLocalChains?: boolean;
 }
 
+export interface ManglePropertiesOption {
+    /**
+     * the regular expression selecting the properties to rename, e.g. `"^_"`
+     */
+    regex: string;
+    /**
+     * the properties never renamed, besides the paths of `ignoreWords`
+     * @default []
+     */
+    reserved?: string[];
+    /**
+     * the names the properties took in previous builds or chunks, property => name.
+     *
+     * a property keeps its name, new properties never take a cached name. a property left
+     * as it is is cached as itself, e.g. `"_foo": "_foo"`, so the other chunks leave it too,
+     * and a renamed one is renamed in the strings naming it, e.g. `"_foo" in obj`, but not
+     * in the other strings.
+     * the transform fails when a property can not keep its cached name, e.g. a property left
+     * as it is in the chunk already has it.
+     * the updated cache is returned in `TransformResult.propertyCache`
+     */
+    nameCache?: Record<string, string>;
+}
+
+export interface SizeBudgetResult {
+    budget: number;
+    /**
+     * bytes of the output
+     */
+    size: number;
+    met: boolean;
+    /**
+     * `budget - size`, negative when the output does not fit
+     */
+    remaining: number;
+}
+
+export type Fallback = "rawSizeNotReduced" | "compressedSizeNotReduced";
+
 export interface TransformResult {
     code: string;
     map?: string;
+    /**
+     * only present when `sizeBudget` is set
+     */
+    sizeBudget?: SizeBudgetResult;
+    /**
+     * why the input is returned untouched, absent when it is transformed
+     */
+    fallback?: Fallback;
+    /**
+     * `nameCache` with the names of the newly hoisted strings, only present when it is set
+     */
+    nameCache?: Record<string, string>;
+    /**
+     * `mangleProperties.nameCache` with the newly renamed and kept properties,
+     * only present when `mangleProperties` is set
+     */
+    propertyCache?: Record<string, string>;
 }
 
 export async function transform(
@@ -109,5 +323,9 @@ export async function transform(
     return {
         code: result.content,
         map: result.map,
+        sizeBudget: result.sizeBudget,
+        fallback: result.fallback as Fallback | undefined,
+        nameCache: result.nameCache,
+        propertyCache: result.propertyCache,
     };
 }
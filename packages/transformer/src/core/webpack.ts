@@ -36,11 +36,30 @@ export class OOMPlugin {
                         enableSourceMap = Boolean(compiler.options.devtool),
                         ignoreWords,
                         preserveKeywords,
+                        reserved,
+                        objective,
+                        asciiOnly,
+                        costStrategy,
+                        sizeBudget,
+                        allowLarger,
+                        charFrequency,
+                        composeStrings,
+                        nameCache,
+                        globalAliases,
+                        factorObjectShapes,
+                        manglePrivateNames,
+                        mangleProperties,
+                        aliasLocalChains,
                         enableCache = true,
                     } = this.options;
-                    const cache = enableCache
-                        ? compilation.getCache(PLUGIN_NAME)
-                        : undefined;
+                    // shared by every chunk, so they rename a property alike
+                    const propertyCache =
+                        mangleProperties && (mangleProperties.nameCache ??= {});
+                    // a cached chunk would not tell the others what it renamed
+                    const cache =
+                        enableCache && !mangleProperties
+                            ? compilation.getCache(PLUGIN_NAME)
+                            : undefined;
 
                     const { SourceMapSource, RawSource } =
                         compiler.webpack.sources;
@@ -89,10 +108,36 @@ export class OOMPlugin {
                                 enableSourceMap,
                                 ignoreWords,
                                 preserveKeywords,
+                                reserved,
+                                objective,
+                                asciiOnly,
+                                costStrategy,
+                                sizeBudget,
+                                allowLarger,
+                                charFrequency,
+                                composeStrings,
+                                nameCache,
+                                globalAliases,
+                                factorObjectShapes,
+                                manglePrivateNames,
+                                mangleProperties,
+                                aliasLocalChains,
                             };
 
                             const result = await transform(inputCode, options);
 
+                            // shared by every chunk and the next builds
+                            if (nameCache && result.nameCache) {
+                                Object.assign(nameCache, result.nameCache);
+                            }
+
+                            if (propertyCache && result.propertyCache) {
+                                Object.assign(
+                                    propertyCache,
+                                    result.propertyCache
+                                );
+                            }
+
                             const code = result.code ?? inputCode;
                             const outputMap = result.map ?? map;
 
@@ -3,6 +3,8 @@ import { TransformOption } from "./core/bridge";
 export enum ModuleType {
     TypeScript = "typescript",
     JavaScript = "javascript",
+    Jsx = "jsx",
+    Tsx = "tsx",
 }
 
 export type Filter = string | RegExp | ((filename: string) => boolean);
@@ -10,7 +12,23 @@ export type Filter = string | RegExp | ((filename: string) => boolean);
 export interface OOMPluginOptions
     extends Pick<
         TransformOption,
-        "enableSourceMap" | "ignoreWords" | "preserveKeywords"
+        | "enableSourceMap"
+        | "ignoreWords"
+        | "preserveKeywords"
+        | "reserved"
+        | "objective"
+        | "asciiOnly"
+        | "costStrategy"
+        | "sizeBudget"
+        | "allowLarger"
+        | "charFrequency"
+        | "composeStrings"
+        | "nameCache"
+        | "globalAliases"
+        | "factorObjectShapes"
+        | "manglePrivateNames"
+        | "mangleProperties"
+        | "aliasLocalChains"
     > {
     /**
      * exclude files that match the filter
@@ -21,6 +39,7 @@ export interface OOMPluginOptions
      */
     include?: Filter[];
     /**
+     * always off with `mangleProperties`, every chunk has to be renamed with the others
      * @default true
      */
     enableCache?: boolean;
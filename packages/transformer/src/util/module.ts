@@ -1,17 +1,27 @@
 import { Filter, ModuleType, OOMPluginOptions } from "../type";
 
-const tsRe = /\.tsx?$/;
-const jsRe = /\.jsx?$/;
+const tsRe = /\.[mc]?ts$/;
+const tsxRe = /\.tsx$/;
+const jsRe = /\.[mc]?js$/;
+const jsxRe = /\.jsx$/;
 
 export function moduleTypeFromName(name: string): ModuleType | undefined {
     if (tsRe.test(name)) {
         return ModuleType.TypeScript;
     }
 
+    if (tsxRe.test(name)) {
+        return ModuleType.Tsx;
+    }
+
     if (jsRe.test(name)) {
         return ModuleType.JavaScript;
     }
 
+    if (jsxRe.test(name)) {
+        return ModuleType.Jsx;
+    }
+
     return undefined;
 }
 